@@ -0,0 +1,453 @@
+use crate::asm::{Error, ErrorKind, Field, FieldError, SymbolTable};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{
+    BrType, Directive, DirectiveType, Immediate, Instruction, InstructionType, LabelReference,
+    Register, Span, StringLiteral,
+};
+use either::Either;
+use getset::Getters;
+
+/// A contiguous block of machine code starting at a `.ORIG` address.
+#[derive(Debug, Getters)]
+pub struct Section {
+    #[get = "pub"]
+    origin: u16,
+    #[get = "pub"]
+    words: Vec<u16>,
+}
+
+#[derive(Debug, Getters)]
+pub struct Object {
+    #[get = "pub"]
+    sections: Vec<Section>,
+    #[get = "pub"]
+    symbol_table: SymbolTable,
+}
+
+pub struct Assembler<'a> {
+    program: &'a Program,
+    symbol_table: SymbolTable,
+    errors: Vec<Error>,
+}
+
+impl<'a> Assembler<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            symbol_table: SymbolTable::default(),
+            errors: vec![],
+        }
+    }
+
+    pub fn assemble(&mut self) -> Result<Object, Vec<Error>> {
+        let (symbol_table, mut errors) = SymbolTable::resolve(self.program);
+        self.symbol_table = symbol_table;
+        self.errors.append(&mut errors);
+        let sections = self.encode();
+        if self.errors.is_empty() {
+            Ok(Object {
+                sections,
+                symbol_table: std::mem::take(&mut self.symbol_table),
+            })
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // second pass, encodes every item into its section
+    fn encode(&mut self) -> Vec<Section> {
+        let mut sections: Vec<Section> = vec![];
+        let mut in_section = false;
+        for item in self.program.items() {
+            match item {
+                ProgramItem::Comment(..) | ProgramItem::EOL(..) | ProgramItem::Error(..) => {}
+                ProgramItem::Instruction(_, instruction, ..) => {
+                    if !in_section {
+                        self.push_error(ErrorKind::OutsideOrig, instruction.span());
+                        continue;
+                    }
+                    let section = sections.last_mut().unwrap();
+                    let address = section.origin as u32 + section.words.len() as u32;
+                    let word = self.encode_instruction(instruction, address);
+                    sections.last_mut().unwrap().words.push(word);
+                }
+                ProgramItem::Directive(_, directive, ..) => match directive.directive_type() {
+                    DirectiveType::ORIG(address) => {
+                        in_section = true;
+                        sections.push(Section {
+                            origin: *address.value(),
+                            words: vec![],
+                        });
+                    }
+                    DirectiveType::END => {
+                        in_section = false;
+                    }
+                    _ => {
+                        if !in_section {
+                            self.push_error(ErrorKind::OutsideOrig, directive.span());
+                            continue;
+                        }
+                        let mut words = self.encode_directive(directive);
+                        sections.last_mut().unwrap().words.append(&mut words);
+                    }
+                },
+            }
+        }
+        sections
+    }
+
+    fn encode_instruction(&mut self, instruction: &Instruction, address: u32) -> u16 {
+        match instruction.instruction_type() {
+            InstructionType::Add(dr, sr1, sr2_or_imm) => {
+                0x1000 | self.encode_arithmetic(dr, sr1, sr2_or_imm)
+            }
+            InstructionType::And(dr, sr1, sr2_or_imm) => {
+                0x5000 | self.encode_arithmetic(dr, sr1, sr2_or_imm)
+            }
+            InstructionType::Not(dr, sr) => 0x9000 | (reg(dr) << 9) | (reg(sr) << 6) | 0x3F,
+            InstructionType::Ld(dr, label) => {
+                0x2000 | (reg(dr) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::Ldi(dr, label) => {
+                0xA000 | (reg(dr) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::Ldr(dr, base, offset) => {
+                0x6000 | (reg(dr) << 9) | (reg(base) << 6) | self.field(offset, Field::Offset6)
+            }
+            InstructionType::Lea(dr, label) => {
+                0xE000 | (reg(dr) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::St(sr, label) => {
+                0x3000 | (reg(sr) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::Sti(sr, label) => {
+                0xB000 | (reg(sr) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::Str(sr, base, offset) => {
+                0x7000 | (reg(sr) << 9) | (reg(base) << 6) | self.field(offset, Field::Offset6)
+            }
+            InstructionType::Br(br_type, label) => {
+                (condition_codes(br_type) << 9) | self.pc_offset(label, Field::PcOffset9, address)
+            }
+            InstructionType::Jmp(base) => 0xC000 | (reg(base) << 6),
+            InstructionType::Jsr(label) => {
+                0x4800 | self.pc_offset(label, Field::PcOffset11, address)
+            }
+            InstructionType::Jsrr(base) => 0x4000 | (reg(base) << 6),
+            InstructionType::Nop => 0x0000,
+            InstructionType::Rti => 0x8000,
+            InstructionType::Ret => 0xC1C0,
+            InstructionType::Halt => 0xF025,
+            InstructionType::Puts => 0xF022,
+            InstructionType::Getc => 0xF020,
+            InstructionType::Out => 0xF021,
+            InstructionType::In => 0xF023,
+            InstructionType::Trap(vector) => {
+                let value = *vector.value() as i32;
+                0xF000 | self.checked(Field::TrapVect8.check(value), vector.span())
+            }
+        }
+    }
+
+    fn encode_directive(&mut self, directive: &Directive) -> Vec<u16> {
+        match directive.directive_type() {
+            DirectiveType::ORIG(..) | DirectiveType::END => vec![],
+            DirectiveType::FILL(immediate) => {
+                vec![
+                    self.immediate_in_range(immediate, i16::MIN as i32, u16::MAX as i32)
+                        .unwrap_or(0) as u16,
+                ]
+            }
+            DirectiveType::BLKW(immediate) => {
+                let size = self.immediate_in_range(immediate, 0, 0xFFFF).unwrap_or(0);
+                vec![0; size as usize]
+            }
+            DirectiveType::STRINGZ(string) => {
+                let mut words: Vec<u16> = vec![];
+                for character in string_characters(string) {
+                    if character as u32 > 0xFFFF {
+                        self.push_error(
+                            ErrorKind::OutOfRange(character as i32, 0, 0xFFFF),
+                            string.span(),
+                        );
+                    }
+                    words.push(character as u16);
+                }
+                words.push(0);
+                words
+            }
+        }
+    }
+
+    fn encode_arithmetic(
+        &mut self,
+        dr: &Register,
+        sr1: &Register,
+        sr2_or_imm: &Either<Register, Immediate>,
+    ) -> u16 {
+        let operand = match sr2_or_imm {
+            Either::Left(sr2) => reg(sr2),
+            Either::Right(immediate) => 0x20 | self.field(immediate, Field::Imm5),
+        };
+        (reg(dr) << 9) | (reg(sr1) << 6) | operand
+    }
+
+    fn pc_offset(
+        &mut self,
+        operand: &Either<LabelReference, Immediate>,
+        field: Field,
+        address: u32,
+    ) -> u16 {
+        let label = match operand {
+            Either::Left(label) => label,
+            Either::Right(offset) => return self.field(offset, field),
+        };
+        let target = match self.symbol_table.get(label.content()) {
+            Some(target) => target as i32,
+            None => {
+                self.push_error(
+                    ErrorKind::UndefinedLabel(label.content().to_owned()),
+                    label.span(),
+                );
+                return 0;
+            }
+        };
+        let offset = target - (address as i32 + 1);
+        self.checked(field.check_offset(label.content(), offset), label.span())
+    }
+
+    fn field(&mut self, immediate: &Immediate, field: Field) -> u16 {
+        self.checked(field.check(*immediate.value()), immediate.span())
+    }
+
+    // the encoded operand, or 0 after reporting it out of range
+    fn checked(&mut self, encoded: Result<u16, FieldError>, span: &Span) -> u16 {
+        encoded.unwrap_or_else(|field_error| {
+            self.push_error(ErrorKind::OutOfField(field_error), span);
+            0
+        })
+    }
+
+    fn immediate_in_range(&mut self, immediate: &Immediate, min: i32, max: i32) -> Option<i32> {
+        let value = *immediate.value();
+        if value < min || value > max {
+            self.push_error(ErrorKind::OutOfRange(value, min, max), immediate.span());
+            return None;
+        }
+        Some(value)
+    }
+
+    fn push_error(&mut self, kind: ErrorKind, span: &Span) {
+        self.errors.push(Error::new(kind, span));
+    }
+}
+
+impl Section {
+    /// Serializes the section into the big-endian `.obj` format, origin first.
+    pub fn to_obj(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity((self.words.len() + 1) * 2);
+        bytes.extend_from_slice(&self.origin.to_be_bytes());
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+#[inline]
+fn reg(register: &Register) -> u16 {
+    *register.register_type() as u16
+}
+
+fn condition_codes(br_type: &BrType) -> u16 {
+    match br_type {
+        BrType::N => 0b100,
+        BrType::Z => 0b010,
+        BrType::P => 0b001,
+        BrType::Nz => 0b110,
+        BrType::Zp => 0b011,
+        BrType::Np => 0b101,
+        BrType::Nzp | BrType::None => 0b111,
+    }
+}
+
+pub(super) fn string_characters(string: &StringLiteral) -> std::str::Chars<'_> {
+    string.value().chars()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::get_ast;
+
+    fn assemble(content: &str) -> Result<Object, Vec<Error>> {
+        let program = get_ast(content);
+        assert!(program.is_ok());
+        let program = program.unwrap();
+        Assembler::new(&program).assemble()
+    }
+
+    fn assemble_words(content: &str) -> Vec<u16> {
+        let object = match assemble(content) {
+            Ok(object) => object,
+            Err(errors) => panic!("{errors:?}"),
+        };
+        assert_eq!(object.sections().len(), 1);
+        object.sections()[0].words().clone()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let content = r#"
+        .ORIG x3000
+        ADD R1, R2, R3
+        ADD R0, R0, #-1
+        AND R3, R4, xF
+        NOT R1, R2
+        .END"#;
+        assert_eq!(
+            assemble_words(content),
+            vec![0x1283, 0x103F, 0x572F, 0x92BF]
+        );
+    }
+
+    #[test]
+    fn test_pc_offsets() {
+        let content = r#"
+        .ORIG x3000
+        LOOP LD R0, DATA
+        LEA R1, LOOP
+        BRnzp LOOP
+        JSR LOOP
+        HALT
+        DATA .FILL x1234
+        .END"#;
+        assert_eq!(
+            assemble_words(content),
+            vec![0x2004, 0xE3FE, 0x0FFD, 0x4FFC, 0xF025, 0x1234]
+        );
+    }
+
+    #[test]
+    fn test_numeric_pc_offsets() {
+        let content = r#"
+        .ORIG x3000
+        BRnzp #-1
+        LD R0, x5
+        JSR #-1024
+        LEA R2, x1F_DATA
+        x1F_DATA .FILL #0
+        .END"#;
+        assert_eq!(
+            assemble_words(content),
+            vec![0x0FFF, 0x2005, 0x4C00, 0xE400, 0]
+        );
+        // a label spelled like a hex or binary offset is the label
+        let content = r#"
+        .ORIG x3000
+        BR x5
+        LD R0, XA
+        LD R1, B1
+        x5 HALT
+        XA .FILL #7
+        B1 .FILL #7
+        .END"#;
+        assert_eq!(
+            assemble_words(content),
+            vec![0x0E02, 0x2002, 0x2202, 0xF025, 7, 7]
+        );
+        // one that only differs in case, or is spelled like a decimal, as well
+        let content = r#"
+        .ORIG x3000
+        BR xa
+        LD R0, 10
+        XA HALT
+        10 .FILL #7
+        .END"#;
+        assert_eq!(assemble_words(content), vec![0x0E01, 0x2001, 0xF025, 7]);
+        // without one it stays a numeric offset
+        assert_eq!(assemble_words(".ORIG x3000 LD R0, B1 .END"), vec![0x2001]);
+        let errors = assemble(".ORIG x3000 BR #256 .END").unwrap_err();
+        assert!(matches!(
+            errors[0].kind(),
+            ErrorKind::OutOfField(FieldError::Immediate(Field::PcOffset9, 256))
+        ));
+    }
+
+    #[test]
+    fn test_directives() {
+        let content = r#"
+        .ORIG x3000
+        .FILL #-1
+        .BLKW #2
+        .STRINGZ "Hi"
+        .END"#;
+        assert_eq!(assemble_words(content), vec![0xFFFF, 0, 0, 0x48, 0x69, 0]);
+    }
+
+    #[test]
+    fn test_control() {
+        let content = r#"
+        .ORIG x3000
+        JMP R2
+        JSRR R3
+        RET
+        NOP
+        RTI
+        TRAP x21
+        GETC
+        OUT
+        PUTS
+        IN
+        LDR R1, R6, #-32
+        STR R1, R6, #31
+        .END"#;
+        assert_eq!(
+            assemble_words(content),
+            vec![
+                0xC080, 0x40C0, 0xC1C0, 0x0000, 0x8000, 0xF021, 0xF020, 0xF021, 0xF022, 0xF023,
+                0x63A0, 0x739F
+            ]
+        );
+    }
+
+    #[test]
+    fn test_obj_format() {
+        let object = assemble(".ORIG x3000 HALT .END").unwrap();
+        assert_eq!(object.sections()[0].to_obj(), vec![0x30, 0x00, 0xF0, 0x25]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let undefined = assemble(".ORIG x3000 BR NOWHERE .END");
+        assert!(matches!(
+            undefined.err().unwrap()[0].kind(),
+            ErrorKind::UndefinedLabel(..)
+        ));
+        let duplicate = assemble(".ORIG x3000 A HALT A HALT .END");
+        assert!(matches!(
+            duplicate.err().unwrap()[0].kind(),
+            ErrorKind::DuplicateLabel(..)
+        ));
+        let range = assemble(".ORIG x3000 ADD R0, R0, #16 .END");
+        assert!(matches!(
+            range.err().unwrap()[0].kind(),
+            ErrorKind::OutOfField(FieldError::Immediate(Field::Imm5, 16))
+        ));
+        let far = assemble(".ORIG x3000 BR FAR .BLKW #256 FAR HALT .END");
+        assert!(matches!(
+            far.err().unwrap()[0].kind(),
+            ErrorKind::OutOfField(FieldError::Offset(Field::PcOffset9, label, 256)) if label == "FAR"
+        ));
+        let vector = assemble(".ORIG x3000 TRAP x100 .END");
+        assert!(matches!(
+            vector.err().unwrap()[0].kind(),
+            ErrorKind::OutOfField(FieldError::Immediate(Field::TrapVect8, 0x100))
+        ));
+        let outside = assemble("HALT");
+        assert!(matches!(
+            outside.err().unwrap()[0].kind(),
+            ErrorKind::OutsideOrig
+        ));
+    }
+}
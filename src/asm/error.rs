@@ -0,0 +1,77 @@
+use crate::asm::FieldError;
+use crate::ast::raw_ast::Span;
+use crate::diagnostic::Diagnostic;
+use getset::Getters;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    // value, min, max
+    OutOfRange(i32, i32, i32),
+    OutOfField(FieldError),
+    OutsideOrig,
+    LocationOverflow,
+}
+
+#[derive(Debug, Getters)]
+pub struct Error {
+    #[get = "pub"]
+    kind: ErrorKind,
+    #[get = "pub"]
+    span: Span,
+}
+
+impl Error {
+    pub(super) fn new(kind: ErrorKind, span: &Span) -> Self {
+        Self {
+            kind,
+            span: span.clone(),
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::UndefinedLabel(..) => "A001",
+            ErrorKind::DuplicateLabel(..) => "A002",
+            ErrorKind::OutOfRange(..) => "A003",
+            ErrorKind::OutOfField(FieldError::Immediate(..)) => "A004",
+            ErrorKind::OutOfField(FieldError::Offset(..)) => "A005",
+            ErrorKind::OutsideOrig => "A006",
+            ErrorKind::LocationOverflow => "A007",
+        }
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let diagnostic = Diagnostic::error(error.kind().code(), error.kind(), error.span());
+        let label = match error.kind() {
+            ErrorKind::UndefinedLabel(_) => "Label is never defined".to_string(),
+            ErrorKind::DuplicateLabel(_) => "Label is already defined".to_string(),
+            ErrorKind::OutOfRange(value, min, max) => {
+                format!("Value {value} is outside of the range {min}..={max}")
+            }
+            ErrorKind::OutOfField(field_error) => return field_error.annotate(diagnostic),
+            ErrorKind::OutsideOrig => "Cannot be assigned an address".to_string(),
+            ErrorKind::LocationOverflow => "Address is above xFFFF".to_string(),
+        };
+        diagnostic.with_label(label)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UndefinedLabel(label) => write!(f, "Undefined label `{label}`"),
+            ErrorKind::DuplicateLabel(label) => write!(f, "Duplicate label `{label}`"),
+            ErrorKind::OutOfRange(..) => write!(f, "Operand out of range"),
+            ErrorKind::OutOfField(field_error) => write!(f, "{field_error}"),
+            ErrorKind::OutsideOrig => write!(f, "Code outside of a .ORIG block"),
+            ErrorKind::LocationOverflow => write!(f, "Program exceeds the end of memory"),
+        }
+    }
+}
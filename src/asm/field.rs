@@ -0,0 +1,165 @@
+use crate::ast::raw_ast::{Instruction, InstructionType};
+use crate::diagnostic::Diagnostic;
+use either::Either;
+use std::fmt;
+
+/// Operand field of an instruction encoding, named as in the ISA reference.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field {
+    // ADD, AND
+    Imm5,
+    // LDR, STR
+    Offset6,
+    // LD, LDI, LEA, ST, STI, BR
+    PcOffset9,
+    // JSR
+    PcOffset11,
+    // TRAP
+    TrapVect8,
+}
+
+/// An operand that does not fit its field, reported by the assembler and the
+/// semantic analysis alike.
+#[derive(Debug, Clone)]
+pub enum FieldError {
+    // field, value
+    Immediate(Field, i32),
+    // field, label, PC-relative offset to it
+    Offset(Field, String, i32),
+}
+
+impl Field {
+    /// Field the literal or label operand of `instruction` is encoded into.
+    pub fn of(instruction: &Instruction) -> Option<Field> {
+        match instruction.instruction_type() {
+            InstructionType::Add(_, _, Either::Right(_))
+            | InstructionType::And(_, _, Either::Right(_)) => Some(Field::Imm5),
+            InstructionType::Ldr(..) | InstructionType::Str(..) => Some(Field::Offset6),
+            InstructionType::Jsr(_) => Some(Field::PcOffset11),
+            InstructionType::Trap(_) => Some(Field::TrapVect8),
+            _ => instruction.pc_offset().map(|_| Field::PcOffset9),
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            Field::Imm5 => 5,
+            Field::Offset6 => 6,
+            Field::PcOffset9 => 9,
+            Field::PcOffset11 => 11,
+            Field::TrapVect8 => 8,
+        }
+    }
+
+    pub fn min(self) -> i32 {
+        match self {
+            Field::TrapVect8 => 0,
+            _ => -(1 << (self.bits() - 1)),
+        }
+    }
+
+    pub fn max(self) -> i32 {
+        match self {
+            Field::TrapVect8 => (1 << self.bits()) - 1,
+            _ => (1 << (self.bits() - 1)) - 1,
+        }
+    }
+
+    pub fn contains(self, value: i32) -> bool {
+        (self.min()..=self.max()).contains(&value)
+    }
+
+    /// Truncates an in-range value to the field width, two's complement for signed fields.
+    pub fn encode(self, value: i32) -> u16 {
+        (value as u16) & ((1 << self.bits()) - 1)
+    }
+
+    /// Encodes a literal operand, failing when it is out of range.
+    pub fn check(self, value: i32) -> Result<u16, FieldError> {
+        match self.contains(value) {
+            true => Ok(self.encode(value)),
+            false => Err(FieldError::Immediate(self, value)),
+        }
+    }
+
+    /// Encodes the offset to a label, failing when it is out of range.
+    pub fn check_offset(self, label: &str, offset: i32) -> Result<u16, FieldError> {
+        match self.contains(offset) {
+            true => Ok(self.encode(offset)),
+            false => Err(FieldError::Offset(self, label.to_owned(), offset)),
+        }
+    }
+}
+
+impl FieldError {
+    /// Labels the operand, and suggests a way around an unreachable label.
+    pub fn annotate(&self, diagnostic: Diagnostic) -> Diagnostic {
+        match self {
+            FieldError::Immediate(field, value) => diagnostic.with_label(format!(
+                "Value {value} is outside of the range {}..={}",
+                field.min(),
+                field.max()
+            )),
+            FieldError::Offset(field, label, offset) => diagnostic
+                .with_label(format!(
+                    "Offset to `{label}` is {offset}, {field} allows {}..={}",
+                    field.min(),
+                    field.max()
+                ))
+                .with_note(
+                    "Move the label closer, or keep its address in a nearby .FILL and go through a register",
+                ),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::Immediate(field, _) => write!(f, "{field} operand out of range"),
+            FieldError::Offset(field, label, _) => {
+                write!(f, "Label `{label}` is out of {field} range")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Imm5 => write!(f, "imm5"),
+            Field::Offset6 => write!(f, "offset6"),
+            Field::PcOffset9 => write!(f, "PCoffset9"),
+            Field::PcOffset11 => write!(f, "PCoffset11"),
+            Field::TrapVect8 => write!(f, "trapvect8"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!((Field::Imm5.min(), Field::Imm5.max()), (-16, 15));
+        assert_eq!((Field::Offset6.min(), Field::Offset6.max()), (-32, 31));
+        assert_eq!(
+            (Field::PcOffset9.min(), Field::PcOffset9.max()),
+            (-256, 255)
+        );
+        assert_eq!(
+            (Field::PcOffset11.min(), Field::PcOffset11.max()),
+            (-1024, 1023)
+        );
+        assert_eq!((Field::TrapVect8.min(), Field::TrapVect8.max()), (0, 255));
+        assert!(!Field::Imm5.contains(16));
+        assert_eq!(Field::Imm5.encode(-1), 0x1F);
+        assert_eq!(Field::PcOffset9.encode(-2), 0x1FE);
+        assert_eq!(Field::Imm5.check(-16).ok(), Some(0x10));
+        assert!(matches!(
+            Field::PcOffset9.check_offset("FAR", 256),
+            Err(FieldError::Offset(Field::PcOffset9, label, 256)) if label == "FAR"
+        ));
+    }
+}
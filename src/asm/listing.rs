@@ -0,0 +1,155 @@
+use crate::asm::{Object, directive_size};
+use crate::ast::processed_ast::{LineColumn, Program, ProgramItem};
+use crate::ast::raw_ast::DirectiveType;
+use getset::Getters;
+use std::collections::BTreeMap;
+
+/// One row of a listing, either a source line or a continuation word of a multi-word directive.
+#[derive(Debug, Clone, Getters)]
+pub struct ListingRow {
+    #[get = "pub"]
+    address: Option<u16>,
+    #[get = "pub"]
+    word: Option<u16>,
+    #[get = "pub"]
+    line: Option<usize>,
+    #[get = "pub"]
+    source: Option<String>,
+}
+
+#[derive(Debug, Getters)]
+pub struct Listing {
+    #[get = "pub"]
+    rows: Vec<ListingRow>,
+}
+
+impl Listing {
+    pub fn new(program: &Program, object: &Object, source: &str) -> Self {
+        let words = words_by_line(program, object);
+        let mut rows = vec![];
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let mut line_words = words.get(&line).into_iter().flatten();
+            let (address, word) = match line_words.next() {
+                Some((address, word)) => (Some(*address), Some(*word)),
+                None => (None, None),
+            };
+            rows.push(ListingRow {
+                address,
+                word,
+                line: Some(line),
+                source: Some(text.to_owned()),
+            });
+            for (address, word) in line_words {
+                rows.push(ListingRow {
+                    address: Some(*address),
+                    word: Some(*word),
+                    line: None,
+                    source: None,
+                });
+            }
+        }
+        Self { rows }
+    }
+
+    /// Renders the listing as `(address) hex  binary (line) source`.
+    pub fn to_lst(&self) -> String {
+        let mut lst = String::new();
+        for row in &self.rows {
+            match (row.address, row.word) {
+                (Some(address), Some(word)) => {
+                    lst.push_str(&format!("  ({address:04X}) {word:04X}  {word:016b}"))
+                }
+                _ => lst.push_str(&" ".repeat(31)),
+            }
+            if let (Some(line), Some(source)) = (row.line, row.source.as_ref()) {
+                lst.push_str(&format!(" ({line:>4}) {source}"));
+            }
+            lst.push('\n');
+        }
+        lst
+    }
+}
+
+// (address, word) pairs emitted by each source line
+fn words_by_line(program: &Program, object: &Object) -> BTreeMap<usize, Vec<(u16, u16)>> {
+    let mut words: BTreeMap<usize, Vec<(u16, u16)>> = BTreeMap::new();
+    let mut sections = object.sections().iter();
+    let mut section = None;
+    let mut offset = 0usize;
+    let mut emit = |lc: &LineColumn, address: u16, word: u16| {
+        words.entry(*lc.line()).or_default().push((address, word));
+    };
+    for item in program.items() {
+        let (lc, size) = match item {
+            ProgramItem::Instruction(_, _, _, lc) => (lc, 1),
+            ProgramItem::Directive(_, directive, _, lc) => match directive.directive_type() {
+                DirectiveType::ORIG(..) => {
+                    section = sections.next();
+                    offset = 0;
+                    if let Some(section) = section {
+                        // the origin is listed like a word at address zero
+                        emit(lc, 0, *section.origin());
+                    }
+                    continue;
+                }
+                _ => (lc, directive_size(directive) as usize),
+            },
+            ProgramItem::Comment(..) | ProgramItem::EOL(..) | ProgramItem::Error(..) => continue,
+        };
+        let Some(section) = section else {
+            continue;
+        };
+        for (index, word) in section.words().iter().skip(offset).take(size).enumerate() {
+            emit(
+                lc,
+                section.origin().wrapping_add((offset + index) as u16),
+                *word,
+            );
+        }
+        offset += size;
+    }
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asm::Assembler;
+    use crate::ast::get_ast;
+
+    fn listing(content: &str) -> String {
+        let program = get_ast(content);
+        assert!(program.is_ok());
+        let program = program.unwrap();
+        let object = Assembler::new(&program).assemble();
+        assert!(object.is_ok());
+        Listing::new(&program, &object.unwrap(), content).to_lst()
+    }
+
+    #[test]
+    fn test_listing() {
+        let content = ".ORIG x3000\n; comment\nHALT\nTEXT .STRINGZ \"ab\"\n.END\n";
+        assert_eq!(
+            listing(content),
+            "  (0000) 3000  0011000000000000 (   1) .ORIG x3000\n\
+             \x20                               (   2) ; comment\n\
+             \x20 (3000) F025  1111000000100101 (   3) HALT\n\
+             \x20 (3001) 0061  0000000001100001 (   4) TEXT .STRINGZ \"ab\"\n\
+             \x20 (3002) 0062  0000000001100010\n\
+             \x20 (3003) 0000  0000000000000000\n\
+             \x20                               (   5) .END\n"
+        );
+    }
+
+    #[test]
+    fn test_blkw_expansion() {
+        let content = ".ORIG x3000\nBUFFER .BLKW #2\n.END";
+        let lst = listing(content);
+        let lines: Vec<&str> = lst.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("  (3000) 0000"));
+        assert!(lines[1].ends_with("(   2) BUFFER .BLKW #2"));
+        assert_eq!(lines[2], "  (3001) 0000  0000000000000000");
+    }
+}
@@ -0,0 +1,11 @@
+mod assembler;
+mod error;
+mod field;
+mod listing;
+mod symbol_table;
+
+pub use assembler::*;
+pub use error::*;
+pub use field::*;
+pub use listing::*;
+pub use symbol_table::*;
@@ -0,0 +1,285 @@
+use crate::asm::assembler::string_characters;
+use crate::asm::{Error, ErrorKind};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{Directive, DirectiveType, Label, Span};
+use getset::Getters;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Getters)]
+pub struct Symbol {
+    #[get = "pub"]
+    name: String,
+    #[get = "pub"]
+    address: u16,
+    #[get = "pub"]
+    span: Span,
+}
+
+/// Label addresses in definition order.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    indices: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new(program: &Program) -> Result<Self, Vec<Error>> {
+        let (symbol_table, errors) = Self::resolve(program);
+        if errors.is_empty() {
+            Ok(symbol_table)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<u16> {
+        self.indices
+            .get(name)
+            .map(|index| self.symbols[*index].address)
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Renders the table in the `.sym` format written by the classic `lc3as`.
+    pub fn to_sym(&self) -> String {
+        let mut sym = String::new();
+        sym.push_str("// Symbol table\n");
+        sym.push_str("// Scope level 0:\n");
+        sym.push_str("//\tSymbol Name       Page Address\n");
+        sym.push_str("//\t----------------  ------------\n");
+        for symbol in &self.symbols {
+            sym.push_str(&format!(
+                "//\t{:<16}  {:04X}\n",
+                symbol.name, symbol.address
+            ));
+        }
+        sym.push('\n');
+        sym
+    }
+
+    /// First pass of the assembler, assigns an address to every label it can and
+    /// returns the errors alongside.
+    pub fn resolve(program: &Program) -> (Self, Vec<Error>) {
+        let mut symbol_table = SymbolTable::default();
+        let mut errors = vec![];
+        let locations = layout(program, &mut errors);
+        for (item, location) in program.items().iter().zip(locations) {
+            match item {
+                ProgramItem::Comment(..) | ProgramItem::Error(..) => {}
+                ProgramItem::Instruction(labels, ..)
+                | ProgramItem::Directive(labels, ..)
+                | ProgramItem::EOL(labels) => symbol_table.define(labels, location, &mut errors),
+            }
+        }
+        (symbol_table, errors)
+    }
+
+    fn define(&mut self, labels: &[Label], location: Location, errors: &mut Vec<Error>) {
+        // labels sharing a line are stored in reverse source order
+        for label in labels.iter().rev() {
+            let address = match location {
+                Location::Outside => {
+                    errors.push(Error::new(ErrorKind::OutsideOrig, label.span()));
+                    continue;
+                }
+                // the block already reported running past the end of memory
+                Location::Overflowed => continue,
+                Location::At(location) => match u16::try_from(location) {
+                    Ok(address) => address,
+                    // right after the last word of memory
+                    Err(_) => {
+                        errors.push(Error::new(ErrorKind::LocationOverflow, label.span()));
+                        continue;
+                    }
+                },
+            };
+            if self.indices.contains_key(label.name()) {
+                errors.push(Error::new(
+                    ErrorKind::DuplicateLabel(label.name().to_owned()),
+                    label.span(),
+                ));
+            } else {
+                self.indices
+                    .insert(label.name().to_owned(), self.symbols.len());
+                self.symbols.push(Symbol {
+                    name: label.name().to_owned(),
+                    address,
+                    span: label.span().clone(),
+                });
+            }
+        }
+    }
+}
+
+// where the first pass places an item
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Location {
+    // not in any `.ORIG` block
+    Outside,
+    // a label after the last word of memory sits at x10000
+    At(u32),
+    // past the end of memory, in a block whose overflow was already reported
+    Overflowed,
+}
+
+/// Address of every item of `program`, `None` outside of a `.ORIG` block.
+pub fn item_addresses(program: &Program) -> Vec<Option<u16>> {
+    layout(program, &mut vec![])
+        .into_iter()
+        .map(|location| match location {
+            Location::At(location) => u16::try_from(location).ok(),
+            Location::Outside | Location::Overflowed => None,
+        })
+        .collect()
+}
+
+// location of every item, reporting the first item of a block that runs past
+// the end of memory and skipping the rest of that block
+fn layout(program: &Program, errors: &mut Vec<Error>) -> Vec<Location> {
+    let mut locations = Vec::with_capacity(program.items().len());
+    let mut location = Location::Outside;
+    for item in program.items() {
+        match item {
+            ProgramItem::Comment(..) | ProgramItem::EOL(..) | ProgramItem::Error(..) => {
+                locations.push(location)
+            }
+            ProgramItem::Instruction(_, instruction, ..) => {
+                locations.push(location);
+                location = advance(location, 1, instruction.span(), errors);
+            }
+            ProgramItem::Directive(_, directive, ..) => match directive.directive_type() {
+                DirectiveType::ORIG(address) => {
+                    location = Location::At(*address.value() as u32);
+                    locations.push(location);
+                }
+                DirectiveType::END => {
+                    locations.push(location);
+                    location = Location::Outside;
+                }
+                _ => {
+                    locations.push(location);
+                    let size = directive_size(directive);
+                    location = advance(location, size, directive.span(), errors);
+                }
+            },
+        }
+    }
+    locations
+}
+
+fn advance(location: Location, size: u32, span: &Span, errors: &mut Vec<Error>) -> Location {
+    let Location::At(location) = location else {
+        return location;
+    };
+    let location = location + size;
+    if location > 0x10000 {
+        errors.push(Error::new(ErrorKind::LocationOverflow, span));
+        return Location::Overflowed;
+    }
+    Location::At(location)
+}
+
+/// Number of words a directive occupies in memory.
+pub fn directive_size(directive: &Directive) -> u32 {
+    match directive.directive_type() {
+        DirectiveType::ORIG(..) | DirectiveType::END => 0,
+        DirectiveType::FILL(..) => 1,
+        DirectiveType::BLKW(immediate) => (*immediate.value()).max(0) as u32,
+        DirectiveType::STRINGZ(string) => string_characters(string).count() as u32 + 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::get_ast;
+
+    fn symbol_table(content: &str) -> SymbolTable {
+        let program = get_ast(content);
+        assert!(program.is_ok());
+        let symbol_table = SymbolTable::new(&program.unwrap());
+        assert!(symbol_table.is_ok());
+        symbol_table.unwrap()
+    }
+
+    #[test]
+    fn test_addresses() {
+        let content = r#"
+        .ORIG x3000
+        START: HALT
+        BUFFER .BLKW #3
+        TEXT .STRINGZ "abc"
+        AFTER .FILL x0
+        END:
+        .END"#;
+        let symbol_table = symbol_table(content);
+        assert_eq!(symbol_table.get("START"), Some(0x3000));
+        assert_eq!(symbol_table.get("BUFFER"), Some(0x3001));
+        assert_eq!(symbol_table.get("TEXT"), Some(0x3004));
+        assert_eq!(symbol_table.get("AFTER"), Some(0x3008));
+        assert_eq!(symbol_table.get("END"), Some(0x3009));
+        assert_eq!(symbol_table.get("MISSING"), None);
+    }
+
+    #[test]
+    fn test_definition_order() {
+        let symbol_table = symbol_table(".ORIG x4000 B: A: HALT C: .END");
+        let names: Vec<&str> = symbol_table
+            .symbols()
+            .iter()
+            .map(|s| s.name().as_str())
+            .collect();
+        assert_eq!(names, vec!["B", "A", "C"]);
+    }
+
+    #[test]
+    fn test_item_addresses() {
+        let program = get_ast("HALT\n.ORIG x3000\nA .BLKW #2\nHALT\n.END\nHALT");
+        assert!(program.is_ok());
+        assert_eq!(
+            item_addresses(&program.unwrap()),
+            vec![
+                None,
+                Some(0x3000),
+                Some(0x3000),
+                Some(0x3002),
+                Some(0x3003),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_location_overflow() {
+        let program = get_ast(".ORIG xFFFE\nA .BLKW #3\nB HALT\nC HALT\n.END").unwrap();
+        let (symbol_table, errors) = SymbolTable::resolve(&program);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ErrorKind::LocationOverflow));
+        assert_eq!(symbol_table.get("A"), Some(0xFFFE));
+        assert_eq!(symbol_table.get("B"), None);
+        // the next block is laid out again
+        let program = get_ast(".ORIG xFFFF\nHALT\nEND\n.END\n.ORIG x3000\nA HALT\n.END").unwrap();
+        let (symbol_table, errors) = SymbolTable::resolve(&program);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ErrorKind::LocationOverflow));
+        assert_eq!(symbol_table.get("END"), None);
+        assert_eq!(symbol_table.get("A"), Some(0x3000));
+    }
+
+    #[test]
+    fn test_sym_format() {
+        let symbol_table = symbol_table(".ORIG x3000 LOOP BR LOOP DATA .FILL #1 .END");
+        assert_eq!(
+            symbol_table.to_sym(),
+            "// Symbol table\n\
+             // Scope level 0:\n\
+             //\tSymbol Name       Page Address\n\
+             //\t----------------  ------------\n\
+             //\tLOOP              3000\n\
+             //\tDATA              3001\n\
+             \n"
+        );
+    }
+}
@@ -2,4 +2,4 @@ pub mod parse;
 pub mod processed_ast;
 pub mod raw_ast;
 
-pub use parse::get_ast;
+pub use parse::{get_ast, get_ast_recovering};
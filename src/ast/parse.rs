@@ -1,19 +1,364 @@
 use crate::ast::processed_ast::{Program, StandardTransform};
-use crate::ast::raw_ast::parse_ast;
+use crate::ast::raw_ast::{self, ErrorNode, Span, parse_ast};
+use crate::diagnostic::Diagnostic;
 use pest::Parser;
+use pest::error::{Error, ErrorVariant, InputLocation, LineColLocation};
 use pest_derive::Parser;
+use std::ops::Range;
 
 #[derive(Parser)]
 #[grammar = "lc3.pest"]
 struct LC3Parser;
 
 pub fn get_ast(content: &str) -> Result<Program, Box<pest::error::Error<Rule>>> {
-    match LC3Parser::parse(Rule::Program, content) {
-        Ok(pairs) => {
-            let program = parse_ast(pairs.into_iter().next().unwrap());
-            let program = StandardTransform::new(true, content).transform(program);
-            Ok(program)
+    let program = parse_raw(content)?;
+    Ok(StandardTransform::new(true, content).transform(program))
+}
+
+// lines parsed at once by the recovering parser, pest takes time proportional to
+// the whole input to report an error, however early it is
+const WINDOW_LINES: usize = 64;
+
+/// Parses as much of the program as possible, resynchronizing at line
+/// boundaries. Every line that fails to parse in context becomes an error
+/// node and contributes one error, in source order.
+pub fn get_ast_recovering(content: &str) -> (Program, Vec<Error<Rule>>) {
+    let mut masked = content.to_owned();
+    let mut broken: Vec<(Range<usize>, Error<Rule>)> = vec![];
+    // start of the first line not known to parse, everything before it does,
+    // and the number of lines before it
+    let mut resume = 0;
+    let mut resume_line = 0;
+    let mut lines = WINDOW_LINES;
+    // where a window failed, an error only counts once a longer window fails there too
+    let mut failed_at = None;
+    let mut whole = None;
+    loop {
+        let (end, last) = window(&masked, resume, lines);
+        let error = match parse_raw(&masked[resume..end]) {
+            Ok(program) if last => {
+                whole = (resume == 0).then_some(program);
+                break;
+            }
+            Ok(_) => {
+                resume_line += masked[resume..end].matches('\n').count();
+                resume = end;
+                lines = WINDOW_LINES;
+                failed_at = None;
+                continue;
+            }
+            Err(error) => relocate(*error, resume, resume_line),
+        };
+        let start = match error.location {
+            InputLocation::Pos(start) | InputLocation::Span((start, _)) => start,
+        };
+        // the window may end inside an item
+        if !last && failed_at != Some(start) {
+            failed_at = Some(start);
+            lines *= 2;
+            continue;
+        }
+        let (line, error) = broken_line(
+            &masked,
+            (resume, resume_line),
+            line_range(content, start),
+            error,
+        );
+        if masked[line.clone()].trim().is_empty() {
+            // nothing left to skip on that line, give up on the rest of the program
+            broken.push((line, error));
+            masked.truncate(resume);
+            break;
         }
+        masked.replace_range(line.clone(), &" ".repeat(line.len()));
+        let next = (line.end + 1).min(content.len());
+        resume_line += content[resume..next].matches('\n').count();
+        resume = next;
+        lines = WINDOW_LINES;
+        failed_at = None;
+        broken.push((line, error));
+    }
+    // the pieces parsed on their own, so the whole masked program does as well
+    let program = match whole {
+        Some(program) => program,
+        None => parse_raw(&masked).unwrap_or_default(),
+    };
+    broken.sort_by_key(|(range, _)| range.start);
+
+    let nodes = broken
+        .iter()
+        .filter_map(|(range, _)| {
+            let text = &content[range.clone()];
+            let trimmed = text.trim();
+            let start = range.start + (text.len() - text.trim_start().len());
+            (!trimmed.is_empty())
+                .then(|| ErrorNode::new(trimmed, Span::new(start, start + trimmed.len())))
+        })
+        .collect();
+    let program = StandardTransform::new(true, content).transform(program.with_errors(nodes));
+    (
+        program,
+        broken.into_iter().map(|(_, error)| error).collect(),
+    )
+}
+
+fn parse_raw(content: &str) -> Result<raw_ast::Program, Box<Error<Rule>>> {
+    match LC3Parser::parse(Rule::Program, content) {
+        Ok(pairs) => parse_ast(pairs.into_iter().next().unwrap()),
         Err(e) => Err(Box::new(e)),
     }
 }
+
+// The line the item that fails at `line` starts on, with its error. Operands may
+// continue on the next line, so an item left incomplete makes the parser fail on
+// the line after it; the broken line is the one after the last prefix of whole
+// lines from `resume` that still parses.
+fn broken_line(
+    masked: &str,
+    (resume, resume_line): (usize, usize),
+    line: Range<usize>,
+    error: Error<Rule>,
+) -> (Range<usize>, Error<Rule>) {
+    if parse_raw(&masked[resume..line.start]).is_ok() {
+        return (line, error);
+    }
+    let mut line = line;
+    while line.start > resume {
+        let previous = line_range(masked, line.start - 1);
+        if parse_raw(&masked[resume..previous.start]).is_ok() {
+            // the incomplete item, reported where it ends
+            return match parse_raw(&masked[resume..previous.end]) {
+                Err(error) => (previous, relocate(*error, resume, resume_line)),
+                Ok(_) => (previous, error),
+            };
+        }
+        line = previous;
+    }
+    (line, error)
+}
+
+// moves an error found in a slice of the content onto the whole content, the
+// slice starting at byte `offset`, the start of the line after the first `lines`
+fn relocate(mut error: Error<Rule>, offset: usize, lines: usize) -> Error<Rule> {
+    error.location = match error.location {
+        InputLocation::Pos(position) => InputLocation::Pos(offset + position),
+        InputLocation::Span((start, end)) => InputLocation::Span((offset + start, offset + end)),
+    };
+    error.line_col = match error.line_col {
+        LineColLocation::Pos((line, column)) => LineColLocation::Pos((lines + line, column)),
+        LineColLocation::Span((start, start_column), (end, end_column)) => {
+            LineColLocation::Span((lines + start, start_column), (lines + end, end_column))
+        }
+    };
+    error
+}
+
+impl From<&Error<Rule>> for Diagnostic {
+    fn from(error: &Error<Rule>) -> Self {
+        // the character the parser stopped at, none at the end of a line,
+        // which pest shows as a visible control picture
+        let found = match error.line_col {
+            LineColLocation::Pos((_, column)) => error
+                .line()
+                .chars()
+                .nth(column - 1)
+                .filter(|c| !matches!(c, '\n' | '\r' | '␊' | '␍')),
+            LineColLocation::Span(..) => None,
+        };
+        let span = match error.location {
+            InputLocation::Pos(offset) => {
+                Span::new(offset, offset + found.map_or(0, char::len_utf8))
+            }
+            InputLocation::Span((start, end)) => Span::new(start, end),
+        };
+        let diagnostic = Diagnostic::error("P001", "Syntax error", &span);
+        match &error.variant {
+            ErrorVariant::ParsingError {
+                positives,
+                negatives,
+            } => {
+                let diagnostic = if !positives.is_empty() {
+                    diagnostic.with_label(format!("Expected {}", format_rules(positives)))
+                } else if !negatives.is_empty() {
+                    diagnostic.with_label(format!("Unexpected {}", format_rules(negatives)))
+                } else {
+                    diagnostic.with_label("Parsing error")
+                };
+                if !positives.is_empty() && !negatives.is_empty() {
+                    diagnostic.with_note(format!(
+                        "Found `{}`, but expected {}",
+                        found.map_or("???".to_string(), String::from),
+                        format_rules(positives)
+                    ))
+                } else {
+                    diagnostic
+                }
+            }
+            ErrorVariant::CustomError { message } => diagnostic.with_label(message),
+        }
+    }
+}
+
+// Helper function to format rules in a readable way
+fn format_rules(rules: &[Rule]) -> String {
+    if rules.is_empty() {
+        return "nothing".to_string();
+    }
+
+    let rule_strings: Vec<String> = rules.iter().map(|rule| format!("`{:?}`", rule)).collect();
+
+    if rule_strings.len() == 1 {
+        rule_strings[0].clone()
+    } else {
+        let last = rule_strings.last().unwrap();
+        let rest = &rule_strings[..rule_strings.len() - 1];
+        format!("{} or {}", rest.join(", "), last)
+    }
+}
+
+// end of the first `lines` lines from `start`, and whether it is the end of the content
+fn window(content: &str, start: usize, lines: usize) -> (usize, bool) {
+    match content[start..].match_indices('\n').nth(lines - 1) {
+        Some((index, _)) => (start + index + 1, start + index + 1 == content.len()),
+        None => (content.len(), true),
+    }
+}
+
+// byte range of the line containing `offset`, without the line break
+fn line_range(content: &str, offset: usize) -> Range<usize> {
+    let offset = offset.min(content.len());
+    let start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let end = content[offset..]
+        .find('\n')
+        .map_or(content.len(), |index| offset + index);
+    start..end
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::processed_ast::ProgramItem;
+
+    #[test]
+    fn test_recovering() {
+        let content = ".ORIG x3000\nADD R1, R2\nLOOP LD R0, DATA\nLDR R0, #1 ; missing base\nHALT\nDATA .FILL #70000\n.END\n";
+        let (program, errors) = get_ast_recovering(content);
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|error| match error.line_col {
+                pest::error::LineColLocation::Pos((line, _))
+                | pest::error::LineColLocation::Span((line, _), _) => line,
+            })
+            .collect();
+        assert_eq!(lines, vec![2, 4, 6]);
+        let nodes: Vec<_> = program
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                ProgramItem::Error(error, lc) => Some((error.content().as_str(), *lc.line())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("ADD R1, R2", 2),
+                ("LDR R0, #1 ; missing base", 4),
+                ("DATA .FILL #70000", 6)
+            ]
+        );
+        assert_eq!(
+            program
+                .items()
+                .iter()
+                .filter(|item| item.is_instruction())
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_syntax_diagnostic() {
+        let content = ".ORIG x3000\nADD R1, R2\n.END";
+        let error = get_ast(content).unwrap_err();
+        let diagnostic = Diagnostic::from(error.as_ref());
+        assert_eq!(*diagnostic.code(), "P001");
+        assert_eq!(diagnostic.message(), "Syntax error");
+        assert_eq!(
+            diagnostic.primary().message(),
+            "Expected `Register` or `Immediate`"
+        );
+        // operands may continue on the next line, so `.END` is where parsing stops
+        assert_eq!(
+            (*diagnostic.span().start(), *diagnostic.span().end()),
+            (23, 24)
+        );
+    }
+
+    #[test]
+    fn test_syntax_diagnostic_at_line_end() {
+        let (_, errors) = get_ast_recovering(".ORIG x3000\nADD R1, R2\n");
+        let diagnostic = Diagnostic::from(&errors[0]);
+        assert_eq!(
+            (*diagnostic.span().start(), *diagnostic.span().end()),
+            (22, 22)
+        );
+    }
+
+    #[test]
+    fn test_recovering_continued_operands() {
+        let content = ".ORIG x3000\nADD R1, R2,\n    R3\nADD R1 R2 !\nHALT\n.END";
+        let (program, errors) = get_ast_recovering(content);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line(), "ADD R1 R2 !");
+        let nodes: Vec<_> = program
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                ProgramItem::Error(error, _) => Some(error.content().as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(nodes, vec!["ADD R1 R2 !"]);
+        // an incomplete item is the broken line, not the valid one after it
+        let (_, errors) = get_ast_recovering(".ORIG x3000\nADD R1, R2,\nHALT\nADD R1 R2 !\n.END");
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|error| match error.line_col {
+                pest::error::LineColLocation::Pos((line, _))
+                | pest::error::LineColLocation::Span((line, _), _) => line,
+            })
+            .collect();
+        assert_eq!(lines, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_recovering_many() {
+        let content = format!(
+            ".ORIG x3000\n{}.END\n",
+            "ADD R1 R2 !\nLOOP BR LOOP\nADD R1, R2, R3\n".repeat(2000)
+        );
+        let (program, errors) = get_ast_recovering(&content);
+        assert_eq!(errors.len(), 2000);
+        let line = match errors[1999].line_col {
+            LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+        };
+        assert_eq!(line, 2 + 3 * 1999);
+        assert_eq!(
+            program
+                .items()
+                .iter()
+                .filter(|item| item.is_instruction())
+                .count(),
+            4000
+        );
+    }
+
+    #[test]
+    fn test_recovering_valid() {
+        let content = ".ORIG x3000\nADD R1, R2,\n    R3\n.END";
+        let (program, errors) = get_ast_recovering(content);
+        assert!(errors.is_empty());
+        assert!(!program.items().iter().any(ProgramItem::is_error));
+    }
+}
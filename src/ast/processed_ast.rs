@@ -1,26 +1,30 @@
-use crate::ast::raw_ast::{Comment, Directive, Instruction, Label, Span};
+use crate::ast::raw_ast::{Comment, Directive, ErrorNode, Instruction, Label, Span};
 use getset::Getters;
 use pest::Stack;
-use std::collections::HashMap;
 
 #[derive(Debug, Getters)]
 pub struct Program {
     #[get = "pub"]
     items: Vec<ProgramItem>,
+    // byte offset of the start of every line
+    line_starts: Vec<usize>,
+    source: String,
 }
 
 #[derive(Debug)]
 pub struct StandardTransform<'a> {
     label_buffer: Stack<Label>,
     forward_next_comment: bool,
-    look_table: LineColumnLookTable<'a>,
     hybrid_inline_comment: bool,
+    line_starts: Vec<usize>,
+    source: &'a str,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Getters)]
 pub struct LineColumn {
+    #[get = "pub"]
     line: usize,
+    #[get = "pub"]
     column: usize,
 }
 
@@ -30,6 +34,7 @@ enum RawProgramItem {
     Instruction(Vec<Label>, Instruction, Option<Comment>),
     Directive(Vec<Label>, Directive, Option<Comment>),
     Eol(Vec<Label>),
+    Error(ErrorNode),
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +43,24 @@ pub enum ProgramItem {
     Instruction(Vec<Label>, Instruction, Option<Comment>, LineColumn),
     Directive(Vec<Label>, Directive, Option<Comment>, LineColumn),
     EOL(Vec<Label>),
+    // a line the recovering parser skipped
+    Error(ErrorNode, LineColumn),
+}
+
+impl Program {
+    /// 1-based line containing a byte offset of the source.
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset)
+    }
+
+    /// Every source line with the byte offset it starts at, without the line break.
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.line_starts.iter().map(|start| {
+            let line = &self.source[*start..];
+            let line = line.split('\n').next().unwrap_or_default();
+            (*start, line.strip_suffix('\r').unwrap_or(line))
+        })
+    }
 }
 
 impl<'a> StandardTransform<'a> {
@@ -45,8 +68,9 @@ impl<'a> StandardTransform<'a> {
         Self {
             label_buffer: Stack::new(),
             forward_next_comment: true,
-            look_table: LineColumnLookTable::new(file_content),
             hybrid_inline_comment,
+            line_starts: LineColumn::line_starts(file_content),
+            source: file_content,
         }
     }
 
@@ -79,6 +103,8 @@ impl<'a> StandardTransform<'a> {
             } else {
                 labelled_items
             },
+            line_starts: self.line_starts.clone(),
+            source: self.source.to_owned(),
         }
     }
 
@@ -108,24 +134,33 @@ impl<'a> StandardTransform<'a> {
             crate::ast::raw_ast::ProgramItem::Comment(comment) => {
                 Some(RawProgramItem::Comment(comment))
             }
+            crate::ast::raw_ast::ProgramItem::Error(error) => Some(RawProgramItem::Error(error)),
         }
     }
 
+    fn locate(&self, span: &Span) -> LineColumn {
+        LineColumn::locate(self.source, &self.line_starts, *span.start())
+    }
+
     fn add_line_info(&mut self, program_item: RawProgramItem) -> ProgramItem {
         match program_item {
             RawProgramItem::Comment(comment) => {
-                let lc = self.look_table.get_line_and_column(comment.span());
+                let lc = self.locate(comment.span());
                 ProgramItem::Comment(comment, lc)
             }
             RawProgramItem::Instruction(label, instruction, comment) => {
-                let lc = self.look_table.get_line_and_column(instruction.span());
+                let lc = self.locate(instruction.span());
                 ProgramItem::Instruction(label, instruction, comment, lc)
             }
             RawProgramItem::Directive(label, directive, comment) => {
-                let lc = self.look_table.get_line_and_column(directive.span());
+                let lc = self.locate(directive.span());
                 ProgramItem::Directive(label, directive, comment, lc)
             }
             RawProgramItem::Eol(label) => ProgramItem::EOL(label),
+            RawProgramItem::Error(error) => {
+                let lc = self.locate(error.span());
+                ProgramItem::Error(error, lc)
+            }
         }
     }
 
@@ -167,58 +202,30 @@ impl<'a> StandardTransform<'a> {
                 Some(ProgramItem::Directive(labels, directive, comment, lc))
             }
             ProgramItem::EOL(labels) => Some(ProgramItem::EOL(labels)),
+            ProgramItem::Error(error, lc) => Some(ProgramItem::Error(error, lc)),
         }
     }
 }
 
-#[derive(Debug)]
-struct LineColumnLookTable<'a> {
-    line_start_indices: HashMap<usize, (usize, usize)>, // Key: start index, Value: (line number, column start)
-    lines: Vec<&'a str>,
-}
-
-impl<'a> LineColumnLookTable<'a> {
-    // Build a new lookup table based on the file content
-    pub fn new(file_content: &'a str) -> Self {
-        let mut line_start_indices = HashMap::new();
-        let mut char_count = 0; // Keeps track of the starting character index of the line
-        let mut line_number = 1; // Line numbers are 1-based
-
-        for line in file_content.lines() {
-            // Insert the line start index and its corresponding line number and column start
-            line_start_indices.insert(char_count, (line_number, 1));
-            char_count += line.len() + 1; // Increment by the length of the line + 1 for newline character
-            line_number += 1;
-        }
-        let lines: Vec<&str> = file_content.lines().collect();
-
-        LineColumnLookTable {
-            line_start_indices,
-            lines,
-        }
+impl LineColumn {
+    /// Byte offset of the start of every line of `content`.
+    pub fn line_starts(content: &str) -> Vec<usize> {
+        std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect()
     }
 
-    // Function to get the line and column for a given span
-    pub fn get_line_and_column(&self, span: &Span) -> LineColumn {
-        let start = *span.start();
-        // Find the line where the span starts
-        for (start_index, (line_number, _)) in self.line_start_indices.iter() {
-            let line_len = self.lines[*line_number - 1].len();
-            if start >= *start_index && start < *start_index + line_len {
-                // Find the column number
-                let column = start - *start_index + 1;
-                return LineColumn {
-                    line: *line_number,
-                    column,
-                };
-            }
+    /// Line and column of a byte offset, both 1-based, the column counted in
+    /// characters. `line_starts` are those of `content`.
+    pub fn locate(content: &str, line_starts: &[usize], offset: usize) -> Self {
+        let offset = offset.min(content.len());
+        let line = line_starts.partition_point(|start| *start <= offset);
+        LineColumn {
+            line,
+            column: content[line_starts[line - 1]..offset].chars().count() + 1,
         }
-
-        unreachable!()
     }
-}
 
-impl LineColumn {
     pub fn at_the_same_line(&self, other: &LineColumn) -> bool {
         self.line == other.line
     }
@@ -240,4 +247,8 @@ impl ProgramItem {
     pub fn is_eol(&self) -> bool {
         matches!(self, ProgramItem::EOL(..))
     }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, ProgramItem::Error(..))
+    }
 }
@@ -2,9 +2,10 @@ use crate::ast::parse;
 use either::Either;
 use getset::Getters;
 use parse::Rule;
+use pest::error::{Error, ErrorVariant};
 use pest::iterators::Pair;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Program {
     items: Vec<ProgramItem>,
 }
@@ -13,6 +14,66 @@ impl Program {
     pub fn items(self) -> Vec<ProgramItem> {
         self.items
     }
+
+    /// Places error nodes among the parsed items in source order.
+    pub(crate) fn with_errors(mut self, errors: Vec<ErrorNode>) -> Self {
+        for error in errors {
+            let index = self
+                .items
+                .partition_point(|item| item.span().start() < error.span().start());
+            self.items.insert(index, ProgramItem::Error(error));
+        }
+        self
+    }
+
+    // `x5`, `B1` and `10` are both numeric offsets and label names, a defined
+    // label wins. Like the debugger, an exact match comes first and then one that
+    // only differs in case; the reference takes the label's spelling.
+    fn resolve_label_offsets(mut self) -> Self {
+        let labels: Vec<String> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ProgramItem::Label(label) => Some(label.name().to_owned()),
+                _ => None,
+            })
+            .collect();
+        let lookup = |name: &str| {
+            labels
+                .iter()
+                .find(|label| *label == name)
+                .or_else(|| labels.iter().find(|label| label.eq_ignore_ascii_case(name)))
+                .cloned()
+        };
+        for item in &mut self.items {
+            let ProgramItem::Instruction(instruction) = item else {
+                continue;
+            };
+            let Some(operand) = instruction.pc_offset_mut() else {
+                continue;
+            };
+            let reference = match operand {
+                // only operands the grammar would also take as an identifier
+                Either::Right(immediate)
+                    if immediate
+                        .content
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    match lookup(&immediate.content) {
+                        Some(name) => LabelReference {
+                            content: name,
+                            span: immediate.span.clone(),
+                        },
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            *operand = Either::Left(reference);
+        }
+        self
+    }
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -29,6 +90,37 @@ pub enum ProgramItem {
     Label(Label),
     Instruction(Instruction),
     Directive(Directive),
+    Error(ErrorNode),
+}
+
+impl ProgramItem {
+    fn span(&self) -> &Span {
+        match self {
+            ProgramItem::Comment(comment) => comment.span(),
+            ProgramItem::Label(label) => label.span(),
+            ProgramItem::Instruction(instruction) => instruction.span(),
+            ProgramItem::Directive(directive) => directive.span(),
+            ProgramItem::Error(error) => error.span(),
+        }
+    }
+}
+
+/// Source line that failed to parse, kept verbatim by the recovering parser.
+#[derive(Debug, Clone, Getters)]
+pub struct ErrorNode {
+    #[get = "pub"]
+    content: String,
+    #[get = "pub"]
+    span: Span,
+}
+
+impl ErrorNode {
+    pub(crate) fn new(content: &str, span: Span) -> Self {
+        Self {
+            content: content.to_owned(),
+            span,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -47,7 +139,7 @@ pub struct Label {
     span: Span,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RegisterType {
     R0,
     R1,
@@ -96,20 +188,21 @@ pub enum InstructionType {
     And(Register, Register, Either<Register, Immediate>),
     Not(Register, Register),
     // load and store
-    Ld(Register, LabelReference),
-    Ldi(Register, LabelReference),
+    Ld(Register, Either<LabelReference, Immediate>),
+    Ldi(Register, Either<LabelReference, Immediate>),
     Ldr(Register, Register, Immediate),
-    Lea(Register, LabelReference),
-    St(Register, LabelReference),
-    Sti(Register, LabelReference),
+    Lea(Register, Either<LabelReference, Immediate>),
+    St(Register, Either<LabelReference, Immediate>),
+    Sti(Register, Either<LabelReference, Immediate>),
     Str(Register, Register, Immediate),
     // branch
-    Br(BrType, LabelReference),
+    Br(BrType, Either<LabelReference, Immediate>),
     Jmp(Register),
-    Jsr(LabelReference),
+    Jsr(Either<LabelReference, Immediate>),
     Jsrr(Register),
     // control
     Nop,
+    Rti,
     Ret,
     Halt,
     // io
@@ -119,6 +212,8 @@ pub enum InstructionType {
     In,
     // trap
     Trap(HexAddress),
+    // opcode 1101 is reserved and has no mnemonic, it can only be written with .FILL,
+    // the reserved-opcode lint reports such a word when it runs as code
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -129,6 +224,9 @@ pub struct Instruction {
     content: String,
     #[get = "pub"]
     span: Span,
+    // the mnemonic through the last operand
+    #[get = "pub"]
+    full_span: Span,
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -137,6 +235,9 @@ pub struct StringLiteral {
     content: String,
     #[get = "pub"]
     span: Span,
+    // text between the quotes with escape sequences decoded
+    #[get = "pub"]
+    value: String,
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -145,6 +246,19 @@ pub struct Immediate {
     content: String,
     #[get = "pub"]
     span: Span,
+    #[get = "pub"]
+    value: i32,
+    #[get = "pub"]
+    base: Base,
+}
+
+/// Base an immediate was written in, kept so tools can echo it back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Base {
+    Decimal,
+    Hexadecimal,
+    Binary,
+    Character,
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -153,6 +267,8 @@ pub struct HexAddress {
     content: String,
     #[get = "pub"]
     span: Span,
+    #[get = "pub"]
+    value: u16,
 }
 
 #[derive(Debug, Clone)]
@@ -174,29 +290,35 @@ pub struct Directive {
     span: Span,
 }
 
-pub fn parse_ast(pair: Pair<Rule>) -> Program {
+/// Smallest and largest values that fit in a 16-bit word, signed or unsigned.
+pub const WORD_MIN: i32 = i16::MIN as i32;
+pub const WORD_MAX: i32 = u16::MAX as i32;
+
+pub fn parse_ast(pair: Pair<Rule>) -> Result<Program, Box<Error<Rule>>> {
     if pair.as_rule() != Rule::Program {
         unreachable!();
     }
-    Program {
-        items: pair
-            .into_inner()
-            .map_while(|p| parse_program_item(p))
-            .collect(),
+    let mut items = vec![];
+    for pair in pair.into_inner() {
+        match parse_program_item(pair)? {
+            Some(item) => items.push(item),
+            None => break,
+        }
     }
+    Ok(Program { items }.resolve_label_offsets())
 }
 
-fn parse_program_item(pair: Pair<Rule>) -> Option<ProgramItem> {
-    match pair.as_rule() {
+fn parse_program_item(pair: Pair<Rule>) -> Result<Option<ProgramItem>, Box<Error<Rule>>> {
+    Ok(match pair.as_rule() {
         Rule::Comment => Some(ProgramItem::Comment(parse_comment(pair))),
         Rule::Label => Some(ProgramItem::Label(parse_label(pair))),
-        Rule::Instruction => Some(ProgramItem::Instruction(parse_instruction(pair))),
-        Rule::Directive => Some(ProgramItem::Directive(parse_directive(pair))),
+        Rule::Instruction => Some(ProgramItem::Instruction(parse_instruction(pair)?)),
+        Rule::Directive => Some(ProgramItem::Directive(parse_directive(pair)?)),
         Rule::EOI => None,
         _ => {
             unreachable!();
         }
-    }
+    })
 }
 
 fn parse_comment(pair: Pair<Rule>) -> Comment {
@@ -213,17 +335,18 @@ fn parse_label(pair: Pair<Rule>) -> Label {
     }
 }
 
-fn parse_instruction(pair: Pair<Rule>) -> Instruction {
+fn parse_instruction(pair: Pair<Rule>) -> Result<Instruction, Box<Error<Rule>>> {
     assert_eq!(pair.as_rule(), Rule::Instruction);
     let mut inner = pair.into_inner();
     let instruction = inner.next();
     assert!(instruction.is_some());
     let instruction = instruction.unwrap();
+    let full_span = Span::from(instruction.as_span());
     let mut instruction_line = instruction.clone().into_inner();
     let true_instruction = instruction_line.next();
     assert!(true_instruction.is_some());
     let true_instruction = true_instruction.unwrap();
-    Instruction {
+    Ok(Instruction {
         instruction_type: match true_instruction.as_rule() {
             Rule::AddInstruction => {
                 let register1 = instruction_line.next();
@@ -238,7 +361,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 InstructionType::Add(
                     parse_register(register1),
                     parse_register(register2),
-                    parse_register_immediate(register_or_immediate),
+                    parse_register_immediate(register_or_immediate)?,
                 )
             }
             Rule::AndInstruction => {
@@ -254,7 +377,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 InstructionType::And(
                     parse_register(register1),
                     parse_register(register2),
-                    parse_register_immediate(register_or_immediate),
+                    parse_register_immediate(register_or_immediate)?,
                 )
             }
             Rule::NotInstruction => {
@@ -273,7 +396,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_reference = label_reference.unwrap();
                 InstructionType::Ld(
                     parse_register(register),
-                    parse_label_reference(label_reference),
+                    parse_label_reference_immediate(label_reference)?,
                 )
             }
             Rule::LdiInstruction => {
@@ -284,7 +407,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_reference = label_reference.unwrap();
                 InstructionType::Ldi(
                     parse_register(register),
-                    parse_label_reference(label_reference),
+                    parse_label_reference_immediate(label_reference)?,
                 )
             }
             Rule::LdrInstruction => {
@@ -298,7 +421,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 InstructionType::Ldr(
                     parse_register(register1),
                     parse_register(register2),
-                    parse_immediate(immediate),
+                    parse_immediate(immediate)?,
                 )
             }
             Rule::LeaInstruction => {
@@ -309,7 +432,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_reference = label_reference.unwrap();
                 InstructionType::Lea(
                     parse_register(register),
-                    parse_label_reference(label_reference),
+                    parse_label_reference_immediate(label_reference)?,
                 )
             }
             Rule::StInstruction => {
@@ -320,7 +443,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_reference = label_reference.unwrap();
                 InstructionType::St(
                     parse_register(register),
-                    parse_label_reference(label_reference),
+                    parse_label_reference_immediate(label_reference)?,
                 )
             }
             Rule::StiInstruction => {
@@ -331,7 +454,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_reference = label_reference.unwrap();
                 InstructionType::Sti(
                     parse_register(register),
-                    parse_label_reference(label_reference),
+                    parse_label_reference_immediate(label_reference)?,
                 )
             }
             Rule::StrInstruction => {
@@ -345,7 +468,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 InstructionType::Str(
                     parse_register(register1),
                     parse_register(register2),
-                    parse_immediate(immediate),
+                    parse_immediate(immediate)?,
                 )
             }
             Rule::BrInstruction => {
@@ -385,7 +508,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 assert!(label_reference.is_some());
                 let label_reference = label_reference.unwrap();
 
-                InstructionType::Br(br_tp, parse_label_reference(label_reference))
+                InstructionType::Br(br_tp, parse_label_reference_immediate(label_reference)?)
             }
             Rule::JmpInstruction => {
                 let register1 = instruction_line.next();
@@ -397,7 +520,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 let label_ref = instruction_line.next();
                 assert!(label_ref.is_some());
                 let label_ref = label_ref.unwrap();
-                InstructionType::Jsr(parse_label_reference(label_ref))
+                InstructionType::Jsr(parse_label_reference_immediate(label_ref)?)
             }
             Rule::JsrrInstruction => {
                 let register1 = instruction_line.next();
@@ -406,6 +529,7 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
                 InstructionType::Jsrr(parse_register(register1))
             }
             Rule::NopInstruction => InstructionType::Nop,
+            Rule::RtiInstruction => InstructionType::Rti,
             Rule::RetInstruction => InstructionType::Ret,
             Rule::HaltInstruction => InstructionType::Halt,
             Rule::PutsInstruction => InstructionType::Puts,
@@ -424,10 +548,11 @@ fn parse_instruction(pair: Pair<Rule>) -> Instruction {
         },
         content: true_instruction.as_str().to_owned(),
         span: Span::from(true_instruction.as_span()),
-    }
+        full_span,
+    })
 }
 
-fn parse_directive(pair: Pair<Rule>) -> Directive {
+fn parse_directive(pair: Pair<Rule>) -> Result<Directive, Box<Error<Rule>>> {
     assert_eq!(pair.as_rule(), Rule::Directive);
     let mut inner = pair.into_inner();
     let directive_line = inner.next();
@@ -436,17 +561,17 @@ fn parse_directive(pair: Pair<Rule>) -> Directive {
     let directive = directive_line.next();
     assert!(directive.is_some());
     let directive = directive.unwrap();
-    Directive {
+    Ok(Directive {
         directive_type: match directive.as_rule() {
             Rule::StringzDirective => {
                 let string = directive_line.next();
                 assert!(string.is_some());
-                DirectiveType::STRINGZ(parse_string_literal(string.unwrap()))
+                DirectiveType::STRINGZ(parse_string_literal(string.unwrap())?)
             }
             Rule::FillDirective => {
                 let immediate = directive_line.next();
                 assert!(immediate.is_some());
-                DirectiveType::FILL(parse_immediate(immediate.unwrap()))
+                DirectiveType::FILL(parse_immediate(immediate.unwrap())?)
             }
             Rule::OrigDirective => {
                 let address = directive_line.next();
@@ -457,7 +582,7 @@ fn parse_directive(pair: Pair<Rule>) -> Directive {
             Rule::BlkwDirective => {
                 let immediate = directive_line.next();
                 assert!(immediate.is_some());
-                DirectiveType::BLKW(parse_immediate(immediate.unwrap()))
+                DirectiveType::BLKW(parse_immediate(immediate.unwrap())?)
             }
             _ => {
                 unreachable!()
@@ -465,23 +590,88 @@ fn parse_directive(pair: Pair<Rule>) -> Directive {
         },
         content: directive.as_span().as_str().to_owned(),
         span: Span::from(directive.as_span()),
-    }
+    })
 }
 
-fn parse_string_literal(pair: Pair<Rule>) -> StringLiteral {
+fn parse_string_literal(pair: Pair<Rule>) -> Result<StringLiteral, Box<Error<Rule>>> {
     assert_eq!(pair.as_rule(), Rule::StringLiteral);
-    StringLiteral {
+    Ok(StringLiteral {
         content: pair.as_str().to_owned(),
         span: Span::from(pair.as_span()),
+        value: unescape(pair.as_span())?,
+    })
+}
+
+/// Decodes the body of a quoted literal, reporting the first unknown escape.
+fn unescape(span: pest::Span) -> Result<String, Box<Error<Rule>>> {
+    let quoted = span.as_str();
+    let body = &quoted[1..quoted.len() - 1];
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.char_indices();
+    while let Some((_, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        // the grammar guarantees a character after every backslash
+        let (index, escaped) = chars.next().unwrap();
+        value.push(match escaped {
+            'n' => '\n',
+            't' => '\t',
+            '0' => '\0',
+            '"' | '\'' | '\\' => escaped,
+            _ => {
+                // `index` is relative to the body, so in the span it lands on the backslash
+                let escape = span.get(index..index + 1 + escaped.len_utf8()).unwrap();
+                return Err(Box::new(Error::new_from_span(
+                    ErrorVariant::CustomError {
+                        message: format!(
+                            "Unknown escape sequence `{}`, expected one of \\n \\t \\0 \\\" \\' \\\\",
+                            escape.as_str()
+                        ),
+                    },
+                    escape,
+                )));
+            }
+        });
     }
+    Ok(value)
 }
 
-fn parse_immediate(pair: Pair<Rule>) -> Immediate {
+fn parse_immediate(pair: Pair<Rule>) -> Result<Immediate, Box<Error<Rule>>> {
     assert_eq!(pair.as_rule(), Rule::Immediate);
-    Immediate {
-        content: pair.as_str().to_owned(),
+    let content = pair.as_str();
+    let (base, value) = if let Some(digits) = content.strip_prefix(['x', 'X']) {
+        (Base::Hexadecimal, i64::from_str_radix(digits, 16).ok())
+    } else if let Some(digits) = content.strip_prefix(['b', 'B']) {
+        (Base::Binary, i64::from_str_radix(digits, 2).ok())
+    } else if content.starts_with('\'') {
+        let character = unescape(pair.as_span())?.chars().next();
+        (Base::Character, character.map(|c| c as i64))
+    } else {
+        let digits = content.trim_start_matches('#');
+        let digits = digits.strip_prefix('+').unwrap_or(digits);
+        (Base::Decimal, digits.parse::<i64>().ok())
+    };
+    // the grammar only admits digits, so a failed parse means too many of them
+    let value = value
+        .filter(|value| (WORD_MIN as i64..=WORD_MAX as i64).contains(value))
+        .ok_or_else(|| {
+            Box::new(Error::new_from_span(
+                ErrorVariant::CustomError {
+                    message: format!(
+                        "Value `{content}` does not fit in 16 bits, expected {WORD_MIN}..={WORD_MAX}"
+                    ),
+                },
+                pair.as_span(),
+            ))
+        })?;
+    Ok(Immediate {
+        content: content.to_owned(),
         span: Span::from(pair.as_span()),
-    }
+        value: value as i32,
+        base,
+    })
 }
 
 fn parse_hex_address(pair: Pair<Rule>) -> HexAddress {
@@ -489,6 +679,8 @@ fn parse_hex_address(pair: Pair<Rule>) -> HexAddress {
     HexAddress {
         content: pair.as_str().to_owned(),
         span: Span::from(pair.as_span()),
+        // at most four hex digits
+        value: u16::from_str_radix(&pair.as_str()[1..], 16).unwrap(),
     }
 }
 
@@ -513,11 +705,24 @@ fn parse_register(pair: Pair<Rule>) -> Register {
     }
 }
 
-fn parse_register_immediate(pair: Pair<Rule>) -> Either<Register, Immediate> {
+fn parse_register_immediate(
+    pair: Pair<Rule>,
+) -> Result<Either<Register, Immediate>, Box<Error<Rule>>> {
     if pair.as_rule() == Rule::Register {
-        return Either::Left(parse_register(pair));
+        return Ok(Either::Left(parse_register(pair)));
+    }
+    Ok(Either::Right(parse_immediate(pair)?))
+}
+
+fn parse_label_reference_immediate(
+    pair: Pair<Rule>,
+) -> Result<Either<LabelReference, Immediate>, Box<Error<Rule>>> {
+    if pair.as_rule() == Rule::PcOffset {
+        let immediate = pair.into_inner().next();
+        assert!(immediate.is_some());
+        return Ok(Either::Right(parse_immediate(immediate.unwrap())?));
     }
-    Either::Right(parse_immediate(pair))
+    Ok(Either::Left(parse_label_reference(pair)))
 }
 
 fn parse_label_reference(pair: Pair<Rule>) -> LabelReference {
@@ -528,6 +733,96 @@ fn parse_label_reference(pair: Pair<Rule>) -> LabelReference {
     }
 }
 
+impl Label {
+    /// Label name without the optional trailing colon.
+    pub fn name(&self) -> &str {
+        self.content.strip_suffix(":").unwrap_or(&self.content)
+    }
+}
+
+impl Instruction {
+    /// Label or numeric offset operand of a PC-relative instruction.
+    pub fn pc_offset(&self) -> Option<&Either<LabelReference, Immediate>> {
+        match &self.instruction_type {
+            InstructionType::Ld(_, operand)
+            | InstructionType::Ldi(_, operand)
+            | InstructionType::Lea(_, operand)
+            | InstructionType::St(_, operand)
+            | InstructionType::Sti(_, operand)
+            | InstructionType::Br(_, operand)
+            | InstructionType::Jsr(operand) => Some(operand),
+            _ => None,
+        }
+    }
+
+    fn pc_offset_mut(&mut self) -> Option<&mut Either<LabelReference, Immediate>> {
+        match &mut self.instruction_type {
+            InstructionType::Ld(_, operand)
+            | InstructionType::Ldi(_, operand)
+            | InstructionType::Lea(_, operand)
+            | InstructionType::St(_, operand)
+            | InstructionType::Sti(_, operand)
+            | InstructionType::Br(_, operand)
+            | InstructionType::Jsr(operand) => Some(operand),
+            _ => None,
+        }
+    }
+
+    /// Label operand of a PC-relative instruction.
+    pub fn label_reference(&self) -> Option<&LabelReference> {
+        self.pc_offset().and_then(|operand| operand.as_ref().left())
+    }
+
+    /// Register the instruction writes, not counting R7 written by calls and traps.
+    pub fn destination(&self) -> Option<&Register> {
+        match &self.instruction_type {
+            InstructionType::Add(dr, ..)
+            | InstructionType::And(dr, ..)
+            | InstructionType::Not(dr, _)
+            | InstructionType::Ld(dr, _)
+            | InstructionType::Ldi(dr, _)
+            | InstructionType::Ldr(dr, ..)
+            | InstructionType::Lea(dr, _) => Some(dr),
+            _ => None,
+        }
+    }
+
+    /// Whether the instruction overwrites R7 with a return address.
+    pub fn is_call(&self) -> bool {
+        matches!(
+            self.instruction_type,
+            InstructionType::Jsr(_)
+                | InstructionType::Jsrr(_)
+                | InstructionType::Trap(_)
+                | InstructionType::Halt
+                | InstructionType::Puts
+                | InstructionType::Getc
+                | InstructionType::Out
+                | InstructionType::In
+        )
+    }
+
+    /// Whether execution never falls through to the next instruction.
+    pub fn is_unconditional_transfer(&self) -> bool {
+        match &self.instruction_type {
+            InstructionType::Br(BrType::Nzp | BrType::None, _)
+            | InstructionType::Jmp(_)
+            | InstructionType::Ret
+            | InstructionType::Rti
+            | InstructionType::Halt => true,
+            // TRAP x25 is HALT
+            InstructionType::Trap(vector) => *vector.value() == 0x25,
+            _ => false,
+        }
+    }
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
 impl From<pest::Span<'_>> for Span {
     fn from(value: pest::Span) -> Self {
         Span {
@@ -536,3 +831,105 @@ impl From<pest::Span<'_>> for Span {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::get_ast;
+    use crate::ast::processed_ast;
+
+    fn immediates(content: &str) -> Vec<(i32, Base)> {
+        let program = get_ast(content).unwrap();
+        program
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                processed_ast::ProgramItem::Directive(_, directive, ..) => {
+                    match directive.directive_type() {
+                        DirectiveType::FILL(immediate) | DirectiveType::BLKW(immediate) => {
+                            Some((*immediate.value(), *immediate.base()))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_immediate_values() {
+        let content = ".ORIG x3000\n.FILL #-15\n.FILL +15\n.FILL 15\n.FILL xFFFF\n.FILL X1f\n.BLKW #-32768\n.END";
+        assert_eq!(
+            immediates(content),
+            vec![
+                (-15, Base::Decimal),
+                (15, Base::Decimal),
+                (15, Base::Decimal),
+                (0xFFFF, Base::Hexadecimal),
+                (0x1F, Base::Hexadecimal),
+                (-32768, Base::Decimal),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_literals() {
+        let content = ".ORIG x3000\n.FILL 'A'\n.FILL '\\n'\n.FILL b0101\n.FILL '\\''\n.END";
+        assert_eq!(
+            immediates(content),
+            vec![
+                (65, Base::Character),
+                (10, Base::Character),
+                (5, Base::Binary),
+                (39, Base::Character),
+            ]
+        );
+
+        let program = get_ast(r#".ORIG x3000 .STRINGZ "Line\n\t\"q\"\\\0" .END"#).unwrap();
+        let strings: Vec<_> = program
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                processed_ast::ProgramItem::Directive(_, directive, ..) => {
+                    match directive.directive_type() {
+                        DirectiveType::STRINGZ(string) => Some(string.value().clone()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(strings, vec!["Line\n\t\"q\"\\\0"]);
+    }
+
+    #[test]
+    fn test_invalid_escape() {
+        let content = ".ORIG x3000\n.STRINGZ \"ok\\q\"\n.END";
+        let error = get_ast(content).unwrap_err();
+        assert!(
+            error
+                .variant
+                .message()
+                .contains("Unknown escape sequence `\\q`")
+        );
+        let pest::error::InputLocation::Span((start, end)) = error.location else {
+            panic!("expected a span");
+        };
+        assert_eq!(&content[start..end], "\\q");
+        assert!(get_ast(".ORIG x3000 .FILL '\\a' .END").is_err());
+    }
+
+    #[test]
+    fn test_immediate_overflow() {
+        for operand in ["#65536", "#-32769", "x10000", "#99999999999999999999"] {
+            let content = format!(".ORIG x3000\n.FILL {operand}\n.END");
+            let error = get_ast(&content).unwrap_err();
+            assert!(error.variant.message().contains("does not fit in 16 bits"));
+            let pest::error::InputLocation::Span((start, end)) = error.location else {
+                panic!("expected a span");
+            };
+            assert_eq!(&content[start..end], operand);
+        }
+    }
+}
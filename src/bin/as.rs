@@ -0,0 +1,186 @@
+use clap::{Arg, command};
+use lc3_toolchain::asm::{Assembler, Listing, Object};
+use lc3_toolchain::ast::get_ast_recovering;
+use lc3_toolchain::ast::processed_ast::Program;
+use lc3_toolchain::bin_utils;
+use lc3_toolchain::bin_utils::get_relative_path;
+use lc3_toolchain::error::{print_asm_errors, print_errors};
+use std::path::Path;
+use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fs};
+
+const CONFIG_FILENAME_EXTENSION: &str = "asm";
+const OBJECT_FILENAME_EXTENSION: &str = "obj";
+const SYMBOL_FILENAME_EXTENSION: &str = "sym";
+const LISTING_FILENAME_EXTENSION: &str = "lst";
+
+const BIN_NAME: &str = "lc3-toolchain lc3as";
+const ABOUT: &str = "Assembler of LC3, designed for ECE109 Spring 2025. Writes a big-endian .obj
+                        file next to every input file. Exits with 1 if any file fails to assemble.";
+
+static VERBOSE_MODE: AtomicBool = AtomicBool::new(false);
+static SYMBOL_MODE: AtomicBool = AtomicBool::new(false);
+static LISTING_MODE: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    let matches = command!()
+        .name(BIN_NAME)
+        .about(ABOUT)
+        .help_template(
+            "{name} {version}\nAuthor: {author}\n{about}\n\n{usage-heading}\n{usage}\n\n{all-args}",
+        )
+        .arg(
+            Arg::new("file")
+                .help("Relative path to the file or directory containing the files to assemble")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::new("sym")
+                .short('s')
+                .long("sym")
+                .help(r#"Also write the symbol table of every file to a .sym file"#)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("lst")
+                .short('l')
+                .long("lst")
+                .help(
+                    r#"Also write an address, hex and binary listing of every file to a .lst file"#,
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .help(r#"Print verbose output"#)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    VERBOSE_MODE.store(matches.get_flag("verbose"), Ordering::Relaxed);
+    SYMBOL_MODE.store(matches.get_flag("sym"), Ordering::Relaxed);
+    LISTING_MODE.store(matches.get_flag("lst"), Ordering::Relaxed);
+    let file_path = matches
+        .get_one::<String>("file")
+        .expect("File path is required");
+    let file_path = match env::current_dir() {
+        Ok(root) => root.join(file_path),
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    };
+    let file_path = bin_utils::read_filepath(
+        VERBOSE_MODE.load(Ordering::Relaxed),
+        CONFIG_FILENAME_EXTENSION,
+        file_path,
+    );
+
+    let mut success = true;
+
+    for path in file_path {
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let path_buf = get_relative_path(&path);
+                let relative_path = path_buf.as_path();
+                match check_syntax_error(relative_path, &content) {
+                    None => success = false,
+                    Some(program) => match Assembler::new(&program).assemble() {
+                        Ok(object) => {
+                            if !write_object(&path, &object) {
+                                success = false;
+                            }
+                            if SYMBOL_MODE.load(Ordering::Relaxed)
+                                && !write_file(
+                                    &path.with_extension(SYMBOL_FILENAME_EXTENSION),
+                                    object.symbol_table().to_sym().as_bytes(),
+                                )
+                            {
+                                success = false;
+                            }
+                            if LISTING_MODE.load(Ordering::Relaxed)
+                                && !write_file(
+                                    &path.with_extension(LISTING_FILENAME_EXTENSION),
+                                    Listing::new(&program, &object, &content)
+                                        .to_lst()
+                                        .as_bytes(),
+                                )
+                            {
+                                success = false;
+                            }
+                        }
+                        Err(errors) => {
+                            print_asm_errors(
+                                relative_path.to_string_lossy().into_owned().as_str(),
+                                &content,
+                                &errors,
+                            );
+                            success = false;
+                        }
+                    },
+                }
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                success = false;
+            }
+        }
+    }
+
+    if !success {
+        exit(1);
+    }
+}
+
+// print every syntax error or return ast
+fn check_syntax_error(filename: &Path, file_content: &str) -> Option<Program> {
+    let (program, errors) = get_ast_recovering(file_content);
+    if errors.is_empty() {
+        return Some(program);
+    }
+    print_errors(
+        filename.to_string_lossy().into_owned().as_str(),
+        file_content,
+        errors,
+    );
+    None
+}
+
+// a single section is written to `name.obj`, multiple ones to `name.x3000.obj`, ...
+fn write_object(source: &Path, object: &Object) -> bool {
+    let single = object.sections().len() == 1;
+    let mut success = true;
+    for section in object.sections() {
+        let path = if single {
+            source.with_extension(OBJECT_FILENAME_EXTENSION)
+        } else {
+            source.with_extension(format!(
+                "x{:04X}.{}",
+                section.origin(),
+                OBJECT_FILENAME_EXTENSION
+            ))
+        };
+        if !write_file(&path, &section.to_obj()) {
+            success = false;
+        }
+    }
+    success
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> bool {
+    match fs::write(path, contents) {
+        Ok(_) => {
+            if VERBOSE_MODE.load(Ordering::Relaxed) {
+                println!("Wrote {}.", get_relative_path(path).display());
+            }
+            true
+        }
+        Err(err) => {
+            eprintln!("Failed to write file {}, because {err}.", path.display());
+            false
+        }
+    }
+}
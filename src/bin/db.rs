@@ -0,0 +1,92 @@
+use clap::{Arg, command};
+use lc3_toolchain::ast::get_ast_recovering;
+use lc3_toolchain::ast::processed_ast::Program;
+use lc3_toolchain::bin_utils::get_relative_path;
+use lc3_toolchain::db::Debugger;
+use lc3_toolchain::error::{print_asm_errors, print_errors};
+use lc3_toolchain::sim::StdIo;
+use std::path::Path;
+use std::process::exit;
+use std::{env, fs, io};
+
+const BIN_NAME: &str = "lc3-toolchain lc3db";
+const ABOUT: &str = "Debugger of LC3, designed for ECE109 Spring 2025. Assembles the file in
+                        memory and starts an interactive session, type `help` for the commands.";
+
+fn main() {
+    let matches = command!()
+        .name(BIN_NAME)
+        .about(ABOUT)
+        .help_template(
+            "{name} {version}\nAuthor: {author}\n{about}\n\n{usage-heading}\n{usage}\n\n{all-args}",
+        )
+        .arg(
+            Arg::new("file")
+                .help("Relative path to the file to debug")
+                .required(true)
+                .index(1),
+        )
+        .get_matches();
+
+    let file_path = matches
+        .get_one::<String>("file")
+        .expect("File path is required");
+    let file_path = match env::current_dir() {
+        Ok(root) => root.join(file_path),
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    };
+    let content = match fs::read_to_string(&file_path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    };
+    let path_buf = get_relative_path(&file_path);
+    let relative_path = path_buf.as_path();
+    let Some(program) = check_syntax_error(relative_path, &content) else {
+        exit(1);
+    };
+    let mut debugger = match Debugger::new(&program, &content, StdIo) {
+        Ok(debugger) => debugger,
+        Err(errors) => {
+            print_asm_errors(
+                relative_path.to_string_lossy().into_owned().as_str(),
+                &content,
+                &errors,
+            );
+            exit(1);
+        }
+    };
+
+    // stdin is only locked per line, the program reads from it too
+    let input = std::iter::from_fn(|| {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(line)),
+            Err(err) => Some(Err(err)),
+        }
+    });
+    if let Err(err) = debugger.repl(input, &mut io::stdout()) {
+        eprintln!("{err}");
+        exit(1);
+    }
+}
+
+// print every syntax error or return ast
+fn check_syntax_error(filename: &Path, file_content: &str) -> Option<Program> {
+    let (program, errors) = get_ast_recovering(file_content);
+    if errors.is_empty() {
+        return Some(program);
+    }
+    print_errors(
+        filename.to_string_lossy().into_owned().as_str(),
+        file_content,
+        errors,
+    );
+    None
+}
@@ -1,16 +1,15 @@
 use clap::{Arg, command};
-use console::{Style, style};
-use lc3_toolchain::ast::get_ast;
+use lc3_toolchain::ast::get_ast_recovering;
 use lc3_toolchain::bin_utils;
 use lc3_toolchain::bin_utils::get_relative_path;
-use lc3_toolchain::error::print_error;
-use lc3_toolchain::fmt::{FormatStyle, Formatter};
-use serde::{Deserialize, Serialize};
-use similar::{ChangeTag, TextDiff};
+use lc3_toolchain::config::find_config;
+use lc3_toolchain::error::print_errors;
+use lc3_toolchain::fmt;
+use lc3_toolchain::fmt::{CONFIG_FILENAME, DEFAULT_STYLE, FormatStyle, Formatter};
 use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::{env, fmt, fs};
+use std::{env, fs};
 
 static FORMATTED_COUNT: AtomicUsize = AtomicUsize::new(0);
 static FILE_DIFF_COUNT: AtomicUsize = AtomicUsize::new(0);
@@ -65,21 +64,20 @@ fn format_file<'a>(
     filename: &Path,
     file_content: &str,
 ) -> Option<Formatter<'a>> {
-    match get_ast(file_content) {
-        Ok(program) => {
-            let mut formatter = Formatter::new(style);
-            formatter.format(program);
-            Some(formatter)
-        }
-        Err(e) => {
-            print_error(
-                filename.to_string_lossy().into_owned().as_str(),
-                file_content,
-                *e,
-            );
-            None
-        }
+    let (program, errors) = get_ast_recovering(file_content);
+    if errors.is_empty() {
+        let mut formatter = Formatter::new(style);
+        formatter.format(program);
+        return Some(formatter);
     }
+    print_errors(
+        filename.to_string_lossy().into_owned().as_str(),
+        file_content,
+        errors,
+    );
+    // a file that cannot be parsed fails the run, with or without --check
+    FILE_DIFF_COUNT.fetch_add(1, Ordering::Relaxed);
+    None
 }
 
 fn write_file(filename: &Path, formatter: &Formatter) {
@@ -100,77 +98,11 @@ fn write_file(filename: &Path, formatter: &Formatter) {
     }
 }
 
-struct Line(Option<usize>);
-
-impl fmt::Display for Line {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.0 {
-            None => write!(f, "    "),
-            Some(idx) => write!(f, "{:<4}", idx + 1),
-        }
-    }
-}
-
 fn check_file_diff(filename: &Path, file_content: &str, formatter: &Formatter) -> bool {
     let formatted = String::from_utf8_lossy(formatter.contents());
-    let diff = TextDiff::configure()
-        .algorithm(similar::Algorithm::Patience)
-        .diff_lines(formatted.as_ref(), file_content);
-
-    let is_diff = diff.iter_all_changes().next().is_some() && (formatted != file_content);
-
-    if is_diff {
-        println!("File differs: {}", filename.display());
-        for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
-            if idx > 0 {
-                println!("{:-^1$}", "-", 80);
-            }
-            for op in group {
-                for change in diff.iter_inline_changes(op) {
-                    let (sign, s) = match change.tag() {
-                        ChangeTag::Delete => ("-", Style::new().red()),
-                        ChangeTag::Insert => ("+", Style::new().green()),
-                        ChangeTag::Equal => (" ", Style::new().dim()),
-                    };
-                    print!(
-                        "{}{} |{}",
-                        style(Line(change.old_index())).dim(),
-                        style(Line(change.new_index())).dim(),
-                        s.apply_to(sign).bold(),
-                    );
-                    for (emphasized, value) in change.iter_strings_lossy() {
-                        if emphasized {
-                            print!("{}", s.apply_to(value).underlined().on_black());
-                        } else {
-                            print!("{}", s.apply_to(value));
-                        }
-                    }
-                    if change.missing_newline() {
-                        println!();
-                    }
-                }
-            }
-        }
-    }
-
-    is_diff
+    bin_utils::print_diff(filename, formatted.as_ref(), file_content)
 }
 
-const DEFAULT_STYLE: FormatStyle = FormatStyle {
-    indent_directive: 3,
-    indent_instruction: 4,
-    indent_label: 0,
-    indent_min_comment_from_block: 1,
-    space_block_to_comment: 1,
-    space_comment_stick_to_body: 0,
-    space_from_label_block: 1,
-    space_from_start_end_block: 1,
-    colon_after_label: true,
-    fixed_body_comment_indent: false,
-    directive_label_wrap: false,
-};
-
-const CONFIG_FILENAME: &str = "lc3-format.toml";
 const CONFIG_FILENAME_EXTENSION: &str = "asm";
 
 const BIN_NAME: &str = "lc3-toolchain lc3fmt";
@@ -251,108 +183,26 @@ fn get_from_cli() -> (FormatStyle, Vec<PathBuf>) {
     (style, file_path)
 }
 
-#[derive(Default, Serialize, Deserialize)]
-pub struct Config {
-    #[serde(rename = "format-style")]
-    pub format_style: ConfigFormatStyle,
-}
-
-#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub struct ConfigFormatStyle {
-    pub indent_directive: Option<u8>,
-    pub indent_instruction: Option<u8>,
-    pub indent_label: Option<u8>,
-    pub indent_min_comment_from_block: Option<u8>,
-    pub space_block_to_comment: Option<u8>,
-    pub space_comment_stick_to_body: Option<u8>,
-    pub space_from_label_block: Option<u8>,
-    pub space_from_start_end_block: Option<u8>,
-    pub colon_after_label: Option<bool>,
-    pub fixed_body_comment_indent: Option<bool>,
-    pub directive_label_wrap: Option<bool>,
-}
-
 fn read_style(filepath_opt: Option<PathBuf>) -> FormatStyle {
-    let filepath: Option<PathBuf> = match filepath_opt.as_ref() {
-        // read the current one
-        None => match env::current_dir() {
-            Ok(dir) => Some(dir.join(CONFIG_FILENAME)),
-            Err(_) => None,
-        },
+    let filepath = match filepath_opt.as_ref() {
+        // the nearest one from the current directory up
+        None => env::current_dir()
+            .ok()
+            .and_then(|dir| find_config(&dir, CONFIG_FILENAME)),
         Some(path) => Some(path.clone()),
     };
-
-    if filepath.is_none() {
+    let Some(path) = filepath else {
         return DEFAULT_STYLE;
-    }
-
-    let path = filepath.as_ref().unwrap();
-
-    match fs::read_to_string(path) {
-        Ok(content) => match toml::from_str::<Config>(&content) {
-            Ok(config) => config_format_style_to_format_style(&DEFAULT_STYLE, config.format_style),
-            Err(err) => {
-                eprintln!(
-                    "Cannot parse {}! {}, fallback to the default settings",
-                    CONFIG_FILENAME, err
-                );
-                DEFAULT_STYLE
-            }
-        },
+    };
+    match fmt::read_style(&path) {
+        Ok(style) => style,
         Err(err) => {
-            if filepath_opt.is_some() {
-                eprintln!(
-                    "Cannot open {}! {}, fallback to the default settings",
-                    CONFIG_FILENAME, err
-                );
-            }
+            eprintln!("{err}, fallback to the default settings");
             DEFAULT_STYLE
         }
     }
 }
 
-fn config_format_style_to_format_style(
-    default: &FormatStyle,
-    config_format_style: ConfigFormatStyle,
-) -> FormatStyle {
-    FormatStyle {
-        indent_directive: config_format_style
-            .indent_directive
-            .unwrap_or(default.indent_directive),
-        indent_instruction: config_format_style
-            .indent_instruction
-            .unwrap_or(default.indent_instruction),
-        indent_label: config_format_style
-            .indent_label
-            .unwrap_or(default.indent_label),
-        indent_min_comment_from_block: config_format_style
-            .indent_min_comment_from_block
-            .unwrap_or(default.indent_min_comment_from_block),
-        space_block_to_comment: config_format_style
-            .space_block_to_comment
-            .unwrap_or(default.space_block_to_comment),
-        space_comment_stick_to_body: config_format_style
-            .space_comment_stick_to_body
-            .unwrap_or(default.space_comment_stick_to_body),
-        space_from_label_block: config_format_style
-            .space_from_label_block
-            .unwrap_or(default.space_from_label_block),
-        space_from_start_end_block: config_format_style
-            .space_from_start_end_block
-            .unwrap_or(default.space_from_start_end_block),
-        colon_after_label: config_format_style
-            .colon_after_label
-            .unwrap_or(default.colon_after_label),
-        fixed_body_comment_indent: config_format_style
-            .fixed_body_comment_indent
-            .unwrap_or(default.fixed_body_comment_indent),
-        directive_label_wrap: config_format_style
-            .directive_label_wrap
-            .unwrap_or(default.directive_label_wrap),
-    }
-}
-
 fn print_style(style: &FormatStyle) {
     let toml_str = toml::to_string(style).expect("Failed to serialize FormatStyle to TOML");
     println!("{toml_str}");
@@ -1,47 +1,26 @@
 use clap::{Arg, command};
-use lc3_toolchain::ast::get_ast;
-use lc3_toolchain::ast::processed_ast::Program;
+use lc3_toolchain::ast::{get_ast, get_ast_recovering};
 use lc3_toolchain::bin_utils;
-use lc3_toolchain::bin_utils::get_relative_path;
-use lc3_toolchain::error::print_error;
-use lc3_toolchain::lint::{CaseStyle, Error, LintStyle, Linter};
-use serde::{Deserialize, Serialize};
+use lc3_toolchain::bin_utils::{get_relative_path, print_diff};
+use lc3_toolchain::config::find_config;
+use lc3_toolchain::diagnostic::{Diagnostic, Severity, apply_fixes};
+use lc3_toolchain::error::print_diagnostics;
+use lc3_toolchain::lint::{CONFIG_FILENAME, FileReport, LintConfig, LintStyle, OutputFormat};
+use lc3_toolchain::semantic::Analyzer;
 use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{env, fs};
 
-const CONFIG_FILENAME: &str = "lc3-lint.toml";
 const CONFIG_FILENAME_EXTENSION: &str = "asm";
 
 const BIN_NAME: &str = "lc3-toolchain lc3lint";
 const ABOUT: &str = "Linter of LC3, designed for ECE109 Spring 2025. Exits with 0 if input has
-                        correct style. Exits with 1 and prints a diff refactoring is required.";
+                        correct style and every label resolves. Exits with 1 if an error is reported, and
+                        with 2 if only warnings are.";
 
 static VERBOSE_MODE: AtomicBool = AtomicBool::new(false);
 
-const DEFAULT_STYLE: LintStyle = LintStyle {
-    colon_after_label: false,
-    label_style: CaseStyle::ScreamingSnakeCase,
-    instruction_style: CaseStyle::ScreamingSnakeCase,
-    directive_style: CaseStyle::ScreamingSnakeCase,
-};
-
-#[derive(Default, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-struct ConfigLintStyle {
-    colon_after_label: Option<bool>,
-    label_style: Option<CaseStyle>,
-    instruction_style: Option<CaseStyle>,
-    directive_style: Option<CaseStyle>,
-}
-
-#[derive(Default, Serialize, Deserialize)]
-struct Config {
-    #[serde(rename = "lint-style")]
-    lint_style: ConfigLintStyle,
-}
-
 fn main() {
     let matches = command!()
         .name(BIN_NAME)
@@ -72,6 +51,26 @@ fn main() {
                 .help(r#"Dumps a default or minimal config to stdout"#)
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .help(r#"Rewrites the files to fix every style violation that can be fixed"#)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("fix-dry-run")
+                .long("fix-dry-run")
+                .help(r#"Prints the diff --fix would apply without writing the files"#)
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("fix"),
+        )
+        .arg(
+            Arg::new("output-format")
+                .long("output-format")
+                .help(r#"Format of the reported problems, the machine readable ones go to stdout"#)
+                .value_parser(OutputFormat::NAMES)
+                .default_value("human"),
+        )
         .arg(
             Arg::new("verbose")
                 .long("verbose")
@@ -96,52 +95,58 @@ fn main() {
         CONFIG_FILENAME_EXTENSION,
         file_path,
     );
-    let style = read_style(matches.get_one::<String>("config-path").map(PathBuf::from));
+    let config = read_config(matches.get_one::<String>("config-path").map(PathBuf::from));
+    let style = *config.style();
+    let output_format: OutputFormat = matches
+        .get_one::<String>("output-format")
+        .expect("Output format has a default")
+        .parse()
+        .unwrap();
 
     if matches.get_flag("print-config") {
         print_style(&style);
     }
 
-    let mut success = true;
+    let fix = matches.get_flag("fix");
+    let fix_dry_run = matches.get_flag("fix-dry-run");
+
+    // most severe problem found in any file
+    let mut worst: Option<Severity> = None;
+    let mut reports = vec![];
 
     for path in file_path {
         match fs::read_to_string(&path) {
-            Ok(content) => {
-                let path_buf = get_relative_path(&path);
-                let relative_path = path_buf.as_path();
-                match check_syntax_error(relative_path, &content) {
-                    None => {}
-                    Some(program) => {
-                        let results = Linter::new(style, program).check();
-                        match results {
-                            Ok(_) => {}
-                            Err(errors) => {
-                                // Visualize errors using codespan-reporting
-                                let mut files = codespan_reporting::files::SimpleFiles::new();
-                                let file_id = files.add(relative_path.to_string_lossy(), &content);
-
-                                let config = codespan_reporting::term::Config::default();
-                                let writer =
-                                    codespan_reporting::term::termcolor::StandardStream::stderr(
-                                        codespan_reporting::term::termcolor::ColorChoice::Auto,
-                                    );
-
-                                for error in errors {
-                                    let diagnostic = create_diagnostic_from_error(&error, file_id);
-                                    codespan_reporting::term::emit(
-                                        &mut writer.lock(),
-                                        &config,
-                                        &files,
-                                        &diagnostic,
-                                    )
-                                    .expect("Failed to emit diagnostic");
-                                }
-
-                                success = false;
-                            }
+            Ok(mut content) => {
+                let relative_path = get_relative_path(&path).to_string_lossy().into_owned();
+                if fix || fix_dry_run {
+                    if let Some((fixed, applied)) = fix_content(&config, &content) {
+                        if fix_dry_run {
+                            print_diff(Path::new(&relative_path), &content, &fixed);
+                        } else if let Err(err) = fs::write(&path, &fixed) {
+                            eprintln!("Failed to write file {relative_path}, because {err}.");
+                        } else {
+                            eprintln!(
+                                "Fixed {} problem{} in {}.",
+                                applied,
+                                if applied > 1 { "s" } else { "" },
+                                relative_path
+                            );
+                            content = fixed;
                         }
                     }
                 }
+                let diagnostics = check(&config, &content);
+                if diagnostics.is_empty() {
+                    continue;
+                }
+                let severity = diagnostics.iter().map(|d| *d.severity()).max();
+                worst = worst.max(severity);
+                match output_format {
+                    OutputFormat::Human => {
+                        print_diagnostics(&relative_path, &content, &diagnostics)
+                    }
+                    _ => reports.push(FileReport::new(relative_path, content, diagnostics)),
+                }
             }
             Err(err) => {
                 if VERBOSE_MODE.load(Ordering::Relaxed) {
@@ -151,115 +156,74 @@ fn main() {
         }
     }
 
-    if !success {
-        exit(1);
+    if let Some(output) = output_format.render(&reports) {
+        print!("{output}");
     }
-}
 
-// print or return ast
-fn check_syntax_error(filename: &Path, file_content: &str) -> Option<Program> {
-    match get_ast(file_content) {
-        Ok(program) => Some(program),
-        Err(e) => {
-            print_error(
-                filename.to_string_lossy().into_owned().as_str(),
-                file_content,
-                *e,
-            );
-            None
-        }
+    match worst {
+        None => {}
+        Some(Severity::Error) => exit(1),
+        Some(Severity::Warning) => exit(2),
     }
 }
 
-fn read_style(filepath_opt: Option<PathBuf>) -> LintStyle {
-    let filepath: Option<PathBuf> = match filepath_opt.as_ref() {
-        // read the current one
-        None => match env::current_dir() {
-            Ok(dir) => Some(dir.join(CONFIG_FILENAME)),
-            Err(_) => None,
-        },
-        Some(path) => Some(path.clone()),
-    };
-
-    if filepath.is_none() {
-        return DEFAULT_STYLE;
+// every syntax error, or the semantic errors followed by the style warnings
+fn check(config: &LintConfig, file_content: &str) -> Vec<Diagnostic> {
+    let (program, errors) = get_ast_recovering(file_content);
+    if !errors.is_empty() {
+        return errors.iter().map(Diagnostic::from).collect();
+    }
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    if let Err(errors) = Analyzer::new(&program).analyze() {
+        diagnostics.extend(errors.iter().map(Diagnostic::from));
     }
+    if let Err(errors) = config.linter(program).check() {
+        diagnostics.extend(errors.iter().map(Diagnostic::from));
+    }
+    diagnostics
+}
 
-    let path = filepath.as_ref().unwrap();
+// the source with every style fix applied, `None` if there is nothing to fix
+fn fix_content(config: &LintConfig, file_content: &str) -> Option<(String, usize)> {
+    let (program, errors) = get_ast_recovering(file_content);
+    if !errors.is_empty() {
+        return None;
+    }
+    let Err(errors) = config.linter(program).check() else {
+        return None;
+    };
+    let (fixed, applied) =
+        apply_fixes(file_content, errors.iter().filter_map(|e| e.fix().as_ref()));
+    // a fix must never turn a valid file into one that does not parse
+    (applied > 0 && get_ast(&fixed).is_ok()).then_some((fixed, applied))
+}
 
-    match fs::read_to_string(path) {
-        Ok(content) => match toml::from_str::<Config>(&content) {
-            Ok(config) => config_lint_style_to_lint_style(&DEFAULT_STYLE, config.lint_style),
-            Err(err) => {
-                eprintln!(
-                    "Cannot parse {}! {}, fallback to the default settings",
-                    CONFIG_FILENAME, err
-                );
-                DEFAULT_STYLE
+fn read_config(filepath_opt: Option<PathBuf>) -> LintConfig {
+    let filepath = match filepath_opt.as_ref() {
+        // the nearest one from the current directory up
+        None => env::current_dir()
+            .ok()
+            .and_then(|dir| find_config(&dir, CONFIG_FILENAME)),
+        Some(path) => Some(path.clone()),
+    };
+    let Some(path) = filepath else {
+        return LintConfig::default();
+    };
+    match LintConfig::read(&path) {
+        Ok(config) => {
+            for id in config.unknown_rules() {
+                eprintln!("Unknown rule `{}` in {}, ignored", id, CONFIG_FILENAME);
             }
-        },
+            config
+        }
         Err(err) => {
-            if filepath_opt.is_some() {
-                eprintln!(
-                    "Cannot open {}! {}, fallback to the default settings",
-                    CONFIG_FILENAME, err
-                );
-            }
-            DEFAULT_STYLE
+            eprintln!("{err}, fallback to the default settings");
+            LintConfig::default()
         }
     }
 }
 
-fn config_lint_style_to_lint_style(
-    default: &LintStyle,
-    config_lint_style: ConfigLintStyle,
-) -> LintStyle {
-    LintStyle {
-        colon_after_label: config_lint_style
-            .colon_after_label
-            .unwrap_or(default.colon_after_label),
-        label_style: config_lint_style.label_style.unwrap_or(default.label_style),
-        instruction_style: config_lint_style
-            .instruction_style
-            .unwrap_or(default.instruction_style),
-        directive_style: config_lint_style
-            .directive_style
-            .unwrap_or(default.directive_style),
-    }
-}
-
 fn print_style(style: &LintStyle) {
     let toml_str = toml::to_string(style).expect("Failed to serialize FormatStyle to TOML");
     println!("{toml_str}");
 }
-
-fn create_diagnostic_from_error(
-    error: &Error,
-    file_id: usize,
-) -> codespan_reporting::diagnostic::Diagnostic<usize> {
-    use codespan_reporting::diagnostic::{Diagnostic, Label};
-
-    // Determine error message based on error type
-    let message = match (error.case_style_error(), error.colon_style_error()) {
-        (Err((expected, found)), _) => match found {
-            Some(found_style) => format!(
-                "Invalid case style: found {:?}, expected {:?}",
-                found_style, expected
-            ),
-            None => format!("Unknown case style, expected {:?}", expected),
-        },
-        (_, Err(_)) => "Invalid colon style".to_string(),
-        _ => "Unknown error".to_string(),
-    };
-
-    // Create the diagnostic with appropriate severity
-    Diagnostic::warning()
-        .with_message(message)
-        .with_labels(vec![
-            Label::primary(file_id, *error.span().start()..*error.span().end())
-                .with_message("Warning occurred here"),
-        ])
-        .with_notes(vec![
-            "See the style guide for more information on formatting rules.".to_string(),
-        ])
-}
@@ -0,0 +1,22 @@
+use lc3_toolchain::fmt::DEFAULT_STYLE as DEFAULT_FORMAT_STYLE;
+use lc3_toolchain::lint::LintConfig;
+use lc3_toolchain::lsp::Server;
+use std::io;
+use std::process::exit;
+
+// speaks the Language Server Protocol over stdio, started by the editor, the
+// defaults apply to documents without a configuration file nearby
+fn main() {
+    let mut server = Server::new(DEFAULT_FORMAT_STYLE, LintConfig::default());
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match server.run(&mut stdin.lock(), &mut stdout.lock()) {
+        // the exit code tells the client whether shutdown preceded exit
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Open(String, io::Error),
+    Parse(String, toml::de::Error),
+}
+
+/// Nearest `filename` in `directory` or one of its parents.
+pub fn find_config(directory: &Path, filename: &str) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .map(|directory| directory.join(filename))
+        .find(|path| path.is_file())
+}
+
+/// Reads and parses a TOML configuration file.
+pub fn read_config<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let filename = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned();
+    let content =
+        fs::read_to_string(path).map_err(|err| ConfigError::Open(filename.clone(), err))?;
+    toml::from_str(&content).map_err(|err| ConfigError::Parse(filename, err))
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Open(filename, err) => write!(f, "Cannot open {filename}! {err}"),
+            ConfigError::Parse(filename, err) => write!(f, "Cannot parse {filename}! {err}"),
+        }
+    }
+}
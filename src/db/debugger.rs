@@ -0,0 +1,496 @@
+use crate::asm::{self, Assembler, SymbolTable, item_addresses};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::DirectiveType;
+use crate::sim::{self, Io, Machine};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Write};
+
+const PROMPT: &str = "(lc3db) ";
+// keeps `continue` from spinning forever on a program that never halts
+const RUN_LIMIT: usize = 10_000_000;
+const LIST_CONTEXT: usize = 2;
+const RET: u16 = 0xC1C0;
+
+const HELP: &str = "\
+break <label|address>     set a breakpoint
+delete [label|address]    remove one or all breakpoints
+watch <label|address>     stop when a memory location changes
+step [count]              execute instructions, entering subroutines
+next                      execute one instruction, stepping over JSR, JSRR and TRAP
+finish                    run until the current subroutine returns
+continue                  run until a breakpoint, watchpoint or HALT
+registers                 print the registers and condition codes
+print <register|label|address>
+x <label|address> [count] print a range of memory
+list                      show the source around the PC
+quit                      exit the debugger
+";
+
+// why execution stopped
+enum Stop {
+    Stepped,
+    Breakpoint,
+    Watchpoint(u16, u16, u16),
+    Halted,
+    StepLimit,
+    Error(sim::Error),
+}
+
+/// Source-level debugger driving a [`Machine`] loaded from an assembled program.
+pub struct Debugger<I: Io> {
+    machine: Machine<I>,
+    symbol_table: SymbolTable,
+    lines: Vec<String>,
+    // address of every instruction or directive mapped to its 1-based source line
+    locations: HashMap<u16, usize>,
+    breakpoints: BTreeSet<u16>,
+    // watched address mapped to its last seen value
+    watchpoints: BTreeMap<u16, u16>,
+}
+
+impl<I: Io> Debugger<I> {
+    /// Assembles `program` in memory and points the PC at its first `.ORIG` block.
+    pub fn new(program: &Program, source: &str, io: I) -> Result<Self, Vec<asm::Error>> {
+        let object = Assembler::new(program).assemble()?;
+        let mut machine = Machine::new(io);
+        // loading sets the PC, so the first section is loaded last
+        for section in object.sections().iter().rev() {
+            machine.load(*section.origin(), section.words());
+        }
+        let mut locations = HashMap::new();
+        for (item, address) in program.items().iter().zip(item_addresses(program)) {
+            let lc = match item {
+                ProgramItem::Instruction(_, _, _, lc) => lc,
+                ProgramItem::Directive(_, directive, _, lc) => match directive.directive_type() {
+                    DirectiveType::ORIG(..) | DirectiveType::END => continue,
+                    _ => lc,
+                },
+                ProgramItem::Comment(..) | ProgramItem::EOL(..) | ProgramItem::Error(..) => {
+                    continue;
+                }
+            };
+            if let Some(address) = address {
+                locations.insert(address, *lc.line());
+            }
+        }
+        Ok(Self {
+            machine,
+            symbol_table: object.symbol_table().clone(),
+            lines: source.lines().map(str::to_owned).collect(),
+            locations,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+        })
+    }
+
+    pub fn machine(&self) -> &Machine<I> {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut Machine<I> {
+        &mut self.machine
+    }
+
+    /// Reads commands until `quit` or end of input.
+    ///
+    /// Takes lines rather than a reader so the program under test can share stdin.
+    pub fn repl(
+        &mut self,
+        input: impl IntoIterator<Item = io::Result<String>>,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        self.show_location(output)?;
+        write!(output, "{PROMPT}")?;
+        output.flush()?;
+        for line in input {
+            if !self.execute(&line?, output)? {
+                return Ok(());
+            }
+            write!(output, "{PROMPT}")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+
+    /// Runs a single command, returns `false` once the session should end.
+    pub fn execute(&mut self, command: &str, output: &mut impl Write) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(true);
+        };
+        let arguments: Vec<&str> = words.collect();
+        match (name.to_lowercase().as_str(), arguments.as_slice()) {
+            ("break" | "b", [location]) => match self.resolve(location) {
+                Some(address) => {
+                    self.breakpoints.insert(address);
+                    writeln!(output, "Breakpoint at {}", self.describe(address))?;
+                }
+                None => writeln!(output, "Unknown label or address `{location}`")?,
+            },
+            ("delete" | "d", []) => {
+                self.breakpoints.clear();
+                writeln!(output, "Deleted all breakpoints")?;
+            }
+            ("delete" | "d", [location]) => match self.resolve(location) {
+                Some(address) if self.breakpoints.remove(&address) => {
+                    writeln!(output, "Deleted breakpoint at {}", self.describe(address))?
+                }
+                Some(address) => writeln!(output, "No breakpoint at {}", self.describe(address))?,
+                None => writeln!(output, "Unknown label or address `{location}`")?,
+            },
+            ("watch" | "w", [location]) => match self.resolve(location) {
+                Some(address) => {
+                    let value = self.machine.peek(address);
+                    self.watchpoints.insert(address, value);
+                    writeln!(output, "Watching {} = x{value:04X}", self.describe(address))?;
+                }
+                None => writeln!(output, "Unknown label or address `{location}`")?,
+            },
+            ("step" | "s", []) => {
+                let stop = self.run_until(1, |_, _| false);
+                self.report(stop, output)?;
+            }
+            ("step" | "s", [count]) => match count.parse::<usize>() {
+                Ok(count) => {
+                    let stop = self.run_until(count, |_, _| false);
+                    self.report(stop, output)?;
+                }
+                Err(_) => writeln!(output, "Invalid count `{count}`")?,
+            },
+            ("next" | "n", []) => {
+                let pc = self.machine.pc();
+                let stop = if is_call(self.machine.peek(pc), &self.machine) {
+                    let ret = pc.wrapping_add(1);
+                    self.run_until(RUN_LIMIT, |_, machine| machine.pc() == ret)
+                } else {
+                    self.run_until(1, |_, _| false)
+                };
+                self.report(stop, output)?;
+            }
+            ("finish" | "fin", []) => {
+                let mut depth = 0usize;
+                let stop = self.run_until(RUN_LIMIT, |instruction, machine| {
+                    if is_call(instruction, machine) {
+                        depth += 1;
+                    } else if instruction == RET {
+                        if depth == 0 {
+                            return true;
+                        }
+                        depth -= 1;
+                    }
+                    false
+                });
+                self.report(stop, output)?;
+            }
+            ("continue" | "c", []) => {
+                let stop = self.run_until(RUN_LIMIT, |_, _| false);
+                self.report(stop, output)?;
+            }
+            ("registers" | "regs" | "r", []) => self.show_registers(output)?,
+            ("print" | "p", [operand]) => self.print(operand, output)?,
+            ("x", [location]) => self.examine(location, 1, output)?,
+            ("x", [location, count]) => match count.parse::<u16>() {
+                Ok(count) => self.examine(location, count, output)?,
+                Err(_) => writeln!(output, "Invalid count `{count}`")?,
+            },
+            ("list" | "l", []) => self.list(output)?,
+            ("help" | "h", []) => write!(output, "{HELP}")?,
+            ("quit" | "q" | "exit", []) => return Ok(false),
+            _ => writeln!(
+                output,
+                "Unknown command `{}`, type `help` for a list of commands",
+                command.trim()
+            )?,
+        }
+        Ok(true)
+    }
+
+    // steps at most `limit` instructions, `done` sees every executed instruction word
+    fn run_until(&mut self, limit: usize, mut done: impl FnMut(u16, &Machine<I>) -> bool) -> Stop {
+        for _ in 0..limit {
+            if self.machine.is_halted() {
+                return Stop::Halted;
+            }
+            let instruction = self.machine.peek(self.machine.pc());
+            if let Err(err) = self.machine.step() {
+                return Stop::Error(err);
+            }
+            for (address, last) in self.watchpoints.iter_mut() {
+                let value = self.machine.peek(*address);
+                if value != *last {
+                    let old = *last;
+                    *last = value;
+                    return Stop::Watchpoint(*address, old, value);
+                }
+            }
+            if self.machine.is_halted() {
+                return Stop::Halted;
+            }
+            if done(instruction, &self.machine) {
+                return Stop::Stepped;
+            }
+            if self.breakpoints.contains(&self.machine.pc()) {
+                return Stop::Breakpoint;
+            }
+        }
+        if limit == RUN_LIMIT {
+            Stop::StepLimit
+        } else {
+            Stop::Stepped
+        }
+    }
+
+    fn report(&self, stop: Stop, output: &mut impl Write) -> io::Result<()> {
+        match stop {
+            Stop::Stepped => {}
+            Stop::Breakpoint => {
+                writeln!(output, "Breakpoint at {}", self.describe(self.machine.pc()))?
+            }
+            Stop::Watchpoint(address, old, new) => writeln!(
+                output,
+                "Watchpoint {}: x{old:04X} -> x{new:04X}",
+                self.describe(address)
+            )?,
+            Stop::Halted => return writeln!(output, "Program halted"),
+            Stop::StepLimit => writeln!(output, "Stopped after {RUN_LIMIT} instructions")?,
+            Stop::Error(err) => writeln!(output, "Error: {err}")?,
+        }
+        self.show_location(output)
+    }
+
+    fn show_location(&self, output: &mut impl Write) -> io::Result<()> {
+        let pc = self.machine.pc();
+        match self.locations.get(&pc) {
+            Some(line) => writeln!(
+                output,
+                "x{pc:04X} {line:>4} | {}",
+                self.lines.get(line - 1).map_or("", |text| text.trim())
+            ),
+            None => writeln!(output, "x{pc:04X}      | (no source)"),
+        }
+    }
+
+    fn show_registers(&self, output: &mut impl Write) -> io::Result<()> {
+        for (index, value) in self.machine.registers().iter().enumerate() {
+            writeln!(output, "R{index}  x{value:04X} {:>6}", *value as i16)?;
+        }
+        let psr = self.machine.psr();
+        let cc = match psr & 0b111 {
+            0b100 => "N",
+            0b010 => "Z",
+            0b001 => "P",
+            _ => "-",
+        };
+        writeln!(output, "PC  x{:04X}", self.machine.pc())?;
+        writeln!(
+            output,
+            "PSR x{psr:04X} CC {cc} ({} mode)",
+            if self.machine.is_user_mode() {
+                "user"
+            } else {
+                "supervisor"
+            }
+        )
+    }
+
+    fn print(&self, operand: &str, output: &mut impl Write) -> io::Result<()> {
+        let upper = operand.to_uppercase();
+        if upper == "PC" {
+            return writeln!(output, "PC = x{:04X}", self.machine.pc());
+        }
+        if let Some(index) = upper
+            .strip_prefix('R')
+            .and_then(|index| index.parse::<usize>().ok())
+            .filter(|index| *index < 8)
+        {
+            let value = self.machine.registers()[index];
+            return writeln!(output, "R{index} = x{value:04X} ({})", value as i16);
+        }
+        match self.resolve(operand) {
+            Some(address) => {
+                let value = self.machine.peek(address);
+                writeln!(
+                    output,
+                    "{} = x{value:04X} ({})",
+                    self.describe(address),
+                    value as i16
+                )
+            }
+            None => writeln!(output, "Unknown register, label or address `{operand}`"),
+        }
+    }
+
+    fn examine(&self, location: &str, count: u16, output: &mut impl Write) -> io::Result<()> {
+        let Some(start) = self.resolve(location) else {
+            return writeln!(output, "Unknown label or address `{location}`");
+        };
+        for offset in 0..count {
+            let address = start.wrapping_add(offset);
+            let value = self.machine.peek(address);
+            let label = self.label_at(address).unwrap_or_default();
+            writeln!(
+                output,
+                "x{address:04X} {label:<16} x{value:04X} {:>6}",
+                value as i16
+            )?;
+        }
+        Ok(())
+    }
+
+    fn list(&self, output: &mut impl Write) -> io::Result<()> {
+        let Some(current) = self.locations.get(&self.machine.pc()) else {
+            return writeln!(output, "No source for x{:04X}", self.machine.pc());
+        };
+        let first = current.saturating_sub(LIST_CONTEXT).max(1);
+        let last = (current + LIST_CONTEXT).min(self.lines.len());
+        for line in first..=last {
+            let marker = if line == *current { "=>" } else { "  " };
+            writeln!(output, "{marker} {line:>4} | {}", self.lines[line - 1])?;
+        }
+        Ok(())
+    }
+
+    // a label name in any case or a number in the `x3000`, `0x3000` or `#12288` notation
+    fn resolve(&self, location: &str) -> Option<u16> {
+        if let Some(address) = self.symbol_table.get(location) {
+            return Some(address);
+        }
+        if let Some(symbol) = self
+            .symbol_table
+            .symbols()
+            .iter()
+            .find(|symbol| symbol.name().eq_ignore_ascii_case(location))
+        {
+            return Some(*symbol.address());
+        }
+        let number = location.strip_prefix('0').unwrap_or(location);
+        if let Some(hex) = number
+            .strip_prefix('x')
+            .or_else(|| number.strip_prefix('X'))
+        {
+            return u16::from_str_radix(hex, 16).ok();
+        }
+        location
+            .strip_prefix('#')
+            .unwrap_or(location)
+            .parse::<u16>()
+            .ok()
+    }
+
+    fn label_at(&self, address: u16) -> Option<&str> {
+        self.symbol_table
+            .symbols()
+            .iter()
+            .find(|symbol| *symbol.address() == address)
+            .map(|symbol| symbol.name().as_str())
+    }
+
+    fn describe(&self, address: u16) -> String {
+        match self.label_at(address) {
+            Some(label) => format!("x{address:04X} ({label})"),
+            None => format!("x{address:04X}"),
+        }
+    }
+}
+
+// JSR, JSRR and TRAPs into a service routine return to the following instruction
+fn is_call<I: Io>(instruction: u16, machine: &Machine<I>) -> bool {
+    match instruction >> 12 {
+        0b0100 => true,
+        // built-in traps complete within a single step
+        0b1111 => machine.peek(instruction & 0xFF) != 0,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::get_ast;
+    use crate::sim::BufferIo;
+    use std::io::BufRead;
+
+    const PROGRAM: &str = r#".ORIG x3000
+        LD R1, COUNT
+LOOP    JSR DOUBLE
+        ADD R1, R1, #-1
+        BRp LOOP
+        ST R0, RESULT
+        HALT
+DOUBLE  ADD R0, R0, #1
+        ADD R0, R0, R0
+        RET
+COUNT   .FILL #3
+RESULT  .BLKW #1
+.END"#;
+
+    fn session(commands: &str) -> (Debugger<BufferIo>, String) {
+        let program = get_ast(PROGRAM);
+        assert!(program.is_ok());
+        let debugger = Debugger::new(&program.unwrap(), PROGRAM, BufferIo::default());
+        assert!(debugger.is_ok());
+        let mut debugger = debugger.unwrap();
+        let mut output = vec![];
+        for command in commands.lines() {
+            assert!(debugger.execute(command, &mut output).unwrap());
+        }
+        (debugger, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_breakpoint_by_label() {
+        let (debugger, output) = session("break DOUBLE\ncontinue");
+        assert_eq!(debugger.machine().pc(), 0x3006);
+        assert!(output.contains("Breakpoint at x3006 (DOUBLE)"));
+        assert!(output.contains("x3006    8 | DOUBLE  ADD R0, R0, #1"));
+        let (debugger, output) = session("break double\ncontinue");
+        assert_eq!(debugger.machine().pc(), 0x3006);
+        assert!(output.contains("Breakpoint at x3006 (DOUBLE)"));
+    }
+
+    #[test]
+    fn test_step_next_finish() {
+        let (debugger, _) = session("step\nstep");
+        assert_eq!(debugger.machine().pc(), 0x3006);
+        let (debugger, _) = session("step\nnext");
+        assert_eq!(debugger.machine().pc(), 0x3002);
+        assert_eq!(debugger.machine().registers()[0], 2);
+        let (debugger, _) = session("break x3007\ncontinue\nfinish");
+        assert_eq!(debugger.machine().pc(), 0x3002);
+        let (debugger, _) = session("step 3");
+        assert_eq!(debugger.machine().pc(), 0x3007);
+    }
+
+    #[test]
+    fn test_watch_and_halt() {
+        let (debugger, output) = session("watch RESULT\ncontinue\ncontinue");
+        assert!(output.contains("Watchpoint x300A (RESULT): x0000 -> x000E"));
+        assert!(output.ends_with("Program halted\n"));
+        assert!(debugger.machine().is_halted());
+    }
+
+    #[test]
+    fn test_inspection() {
+        let (_, output) = session("step\nprint R1\nprint COUNT\nx COUNT 2\nregisters");
+        assert!(output.contains("R1 = x0003 (3)"));
+        assert!(output.contains("x3009 (COUNT) = x0003 (3)"));
+        assert!(output.contains("x3009 COUNT            x0003      3\n"));
+        assert!(output.contains("x300A RESULT           x0000      0\n"));
+        assert!(output.contains("PSR x0001 CC P (supervisor mode)"));
+    }
+
+    #[test]
+    fn test_repl() {
+        let program = get_ast(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(&program, PROGRAM, BufferIo::default()).unwrap();
+        let mut output = vec![];
+        debugger
+            .repl("list\nbogus\nquit\nstep\n".as_bytes().lines(), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("x3000    2 | LD R1, COUNT\n(lc3db) "));
+        assert!(output.contains("=>    2 |         LD R1, COUNT"));
+        assert!(output.contains("Unknown command `bogus`"));
+        assert_eq!(debugger.machine().pc(), 0x3000);
+    }
+}
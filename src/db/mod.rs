@@ -0,0 +1,3 @@
+mod debugger;
+
+pub use debugger::*;
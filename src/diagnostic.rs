@@ -0,0 +1,216 @@
+use crate::ast::raw_ast::Span;
+use getset::Getters;
+use std::fmt;
+
+// diagnostic codes are a module prefix and a number, `P` for the parser,
+// `S` for the semantic analysis, `A` for the assembler and `L` for the linter
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A span with the message shown next to it.
+#[derive(Debug, Clone, Getters)]
+pub struct Annotation {
+    #[get = "pub"]
+    span: Span,
+    #[get = "pub"]
+    message: String,
+}
+
+/// Replacement of the text covered by a span.
+#[derive(Debug, Clone, Getters)]
+pub struct Edit {
+    #[get = "pub"]
+    span: Span,
+    #[get = "pub"]
+    replacement: String,
+}
+
+/// Suggested change that resolves a diagnostic.
+#[derive(Debug, Clone, Getters)]
+pub struct Fix {
+    #[get = "pub"]
+    message: String,
+    #[get = "pub"]
+    edits: Vec<Edit>,
+}
+
+/// Problem reported by any of the tools, rendered, filtered and serialized alike.
+#[derive(Debug, Clone, Getters)]
+pub struct Diagnostic {
+    #[get = "pub"]
+    code: &'static str,
+    // id of the lint rule that reported it, as configured in `lc3-lint.toml`
+    #[get = "pub"]
+    rule: Option<&'static str>,
+    #[get = "pub"]
+    severity: Severity,
+    #[get = "pub"]
+    message: String,
+    #[get = "pub"]
+    primary: Annotation,
+    #[get = "pub"]
+    secondary: Vec<Annotation>,
+    #[get = "pub"]
+    notes: Vec<String>,
+    #[get = "pub"]
+    fix: Option<Fix>,
+}
+
+impl Fix {
+    pub fn new(message: impl ToString, edits: Vec<(Span, String)>) -> Self {
+        Self {
+            message: message.to_string(),
+            edits: edits
+                .into_iter()
+                .map(|(span, replacement)| Edit { span, replacement })
+                .collect(),
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, severity: Severity, message: String, span: &Span) -> Self {
+        Self {
+            code,
+            rule: None,
+            severity,
+            message,
+            primary: Annotation {
+                span: span.clone(),
+                message: String::new(),
+            },
+            secondary: vec![],
+            notes: vec![],
+            fix: None,
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl ToString, span: &Span) -> Self {
+        Self::new(code, Severity::Error, message.to_string(), span)
+    }
+
+    /// Message shown next to the primary span.
+    pub fn with_label(mut self, message: impl ToString) -> Self {
+        self.primary.message = message.to_string();
+        self
+    }
+
+    pub fn with_secondary(mut self, span: &Span, message: impl ToString) -> Self {
+        self.secondary.push(Annotation {
+            span: span.clone(),
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    pub fn with_rule(mut self, rule: &'static str) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.primary.span
+    }
+
+    /// Single line message for tools that cannot show the source.
+    pub fn summary(&self) -> String {
+        // syntax errors carry their detail in the label
+        match self.code {
+            "P001" => format!("{}: {}", self.message, self.primary.message),
+            _ => self.message.clone(),
+        }
+    }
+}
+
+/// Applies every fix whose edits do not overlap the edits of a fix applied
+/// before it. Edits repeated across fixes, such as a renamed reference, are
+/// applied once. Returns the new source and the number of fixes applied.
+pub fn apply_fixes<'a>(source: &str, fixes: impl IntoIterator<Item = &'a Fix>) -> (String, usize) {
+    let mut accepted: Vec<&Edit> = vec![];
+    let mut applied = 0;
+    for fix in fixes {
+        let edits: Vec<&Edit> = fix
+            .edits
+            .iter()
+            .filter(|edit| !accepted.iter().any(|other| edit.same_as(other)))
+            .collect();
+        if edits
+            .iter()
+            .any(|edit| accepted.iter().any(|other| edit.overlaps(other)))
+        {
+            continue;
+        }
+        accepted.extend(edits);
+        applied += 1;
+    }
+    // back to front, so earlier offsets stay valid
+    accepted.sort_by_key(|edit| std::cmp::Reverse(*edit.span.start()));
+    let mut fixed = source.to_owned();
+    for edit in accepted {
+        fixed.replace_range(*edit.span.start()..*edit.span.end(), &edit.replacement);
+    }
+    (fixed, applied)
+}
+
+impl Edit {
+    fn range(&self) -> (usize, usize) {
+        (*self.span.start(), *self.span.end())
+    }
+
+    fn same_as(&self, other: &Edit) -> bool {
+        self.range() == other.range() && self.replacement == other.replacement
+    }
+
+    // insertions at the same offset overlap too, their order would be ambiguous
+    fn overlaps(&self, other: &Edit) -> bool {
+        let ((start, end), (other_start, other_end)) = (self.range(), other.range());
+        start == other_start || (start < other_end && other_start < end)
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "loop ADD R0, R0, #1\nBR loop\n";
+        let rename = Fix::new(
+            "Rename",
+            vec![
+                (Span::new(0, 4), "LOOP".to_string()),
+                (Span::new(23, 27), "LOOP".to_string()),
+            ],
+        );
+        // same reference edit as the rename, applied once
+        let reference = Fix::new("Use", vec![(Span::new(23, 27), "LOOP".to_string())]);
+        // overlaps the rename, skipped
+        let overlapping = Fix::new("Other", vec![(Span::new(2, 8), "X".to_string())]);
+        let (fixed, applied) = apply_fixes(source, [&rename, &reference, &overlapping]);
+        assert_eq!(fixed, "LOOP ADD R0, R0, #1\nBR LOOP\n");
+        assert_eq!(applied, 2);
+    }
+}
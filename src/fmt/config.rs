@@ -0,0 +1,78 @@
+use crate::config::{ConfigError, read_config};
+use crate::fmt::{DEFAULT_STYLE, FormatStyle};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const CONFIG_FILENAME: &str = "lc3-format.toml";
+
+#[derive(Default, Serialize, Deserialize)]
+struct Config {
+    #[serde(rename = "format-style")]
+    format_style: ConfigFormatStyle,
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ConfigFormatStyle {
+    indent_directive: Option<u8>,
+    indent_instruction: Option<u8>,
+    indent_label: Option<u8>,
+    indent_min_comment_from_block: Option<u8>,
+    space_block_to_comment: Option<u8>,
+    space_comment_stick_to_body: Option<u8>,
+    space_from_label_block: Option<u8>,
+    space_from_start_end_block: Option<u8>,
+    colon_after_label: Option<bool>,
+    fixed_body_comment_indent: Option<bool>,
+    directive_label_wrap: Option<bool>,
+}
+
+/// Reads the style from an `lc3-format.toml`, options it leaves out keep their default.
+pub fn read_style(path: &Path) -> Result<FormatStyle, ConfigError> {
+    let config: Config = read_config(path)?;
+    Ok(config_format_style_to_format_style(
+        &DEFAULT_STYLE,
+        config.format_style,
+    ))
+}
+
+fn config_format_style_to_format_style(
+    default: &FormatStyle,
+    config_format_style: ConfigFormatStyle,
+) -> FormatStyle {
+    FormatStyle {
+        indent_directive: config_format_style
+            .indent_directive
+            .unwrap_or(default.indent_directive),
+        indent_instruction: config_format_style
+            .indent_instruction
+            .unwrap_or(default.indent_instruction),
+        indent_label: config_format_style
+            .indent_label
+            .unwrap_or(default.indent_label),
+        indent_min_comment_from_block: config_format_style
+            .indent_min_comment_from_block
+            .unwrap_or(default.indent_min_comment_from_block),
+        space_block_to_comment: config_format_style
+            .space_block_to_comment
+            .unwrap_or(default.space_block_to_comment),
+        space_comment_stick_to_body: config_format_style
+            .space_comment_stick_to_body
+            .unwrap_or(default.space_comment_stick_to_body),
+        space_from_label_block: config_format_style
+            .space_from_label_block
+            .unwrap_or(default.space_from_label_block),
+        space_from_start_end_block: config_format_style
+            .space_from_start_end_block
+            .unwrap_or(default.space_from_start_end_block),
+        colon_after_label: config_format_style
+            .colon_after_label
+            .unwrap_or(default.colon_after_label),
+        fixed_body_comment_indent: config_format_style
+            .fixed_body_comment_indent
+            .unwrap_or(default.fixed_body_comment_indent),
+        directive_label_wrap: config_format_style
+            .directive_label_wrap
+            .unwrap_or(default.directive_label_wrap),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::ast::processed_ast::{Program, ProgramItem};
 use crate::ast::raw_ast::{
-    Comment, Directive, DirectiveType, Immediate, Instruction, InstructionType, Label, Register,
+    Comment, Directive, DirectiveType, Immediate, Instruction, InstructionType, Label,
+    LabelReference, Register,
 };
 use either::Either;
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,21 @@ pub struct FormatStyle {
     pub directive_label_wrap: bool,
 }
 
+/// Style used when no `lc3-format.toml` is found.
+pub const DEFAULT_STYLE: FormatStyle = FormatStyle {
+    indent_directive: 3,
+    indent_instruction: 4,
+    indent_label: 0,
+    indent_min_comment_from_block: 1,
+    space_block_to_comment: 1,
+    space_comment_stick_to_body: 0,
+    space_from_label_block: 1,
+    space_from_start_end_block: 1,
+    colon_after_label: true,
+    fixed_body_comment_indent: false,
+    directive_label_wrap: false,
+};
+
 pub struct Formatter<'a> {
     style: &'a FormatStyle,
     buffer: Vec<u8>,
@@ -157,10 +173,12 @@ impl<'a> Formatter<'a> {
                                 0
                             }
                         }
-                        ProgramItem::EOL(..) | ProgramItem::Comment(..) => 0,
+                        ProgramItem::EOL(..)
+                        | ProgramItem::Comment(..)
+                        | ProgramItem::Error(..) => 0,
                     },
                 },
-                ProgramItem::EOL(..) | ProgramItem::Comment(..) => 0,
+                ProgramItem::EOL(..) | ProgramItem::Comment(..) | ProgramItem::Error(..) => 0,
             };
             paddings += space as usize;
         }
@@ -269,6 +287,8 @@ impl FormattedDisplay for ProgramItem {
                     .collect();
                 (labels, "".to_owned(), None)
             }
+            // unparsed lines are kept as written
+            ProgramItem::Error(error, _) => (vec![], error.content().to_owned(), None),
         }
     }
 }
@@ -296,19 +316,24 @@ fn print_instruction(instruction: &Instruction) -> String {
                 immediate.content()
             )
         }
-        InstructionType::Ld(register1, label_ref)
-        | InstructionType::Ldi(register1, label_ref)
-        | InstructionType::Lea(register1, label_ref)
-        | InstructionType::St(register1, label_ref)
-        | InstructionType::Sti(register1, label_ref) => {
-            format!("{}, {}", register1.content(), label_ref.content())
+        InstructionType::Ld(register1, label_or_offset)
+        | InstructionType::Ldi(register1, label_or_offset)
+        | InstructionType::Lea(register1, label_or_offset)
+        | InstructionType::St(register1, label_or_offset)
+        | InstructionType::Sti(register1, label_or_offset) => {
+            format!(
+                "{}, {}",
+                register1.content(),
+                print_label_or_immediate(label_or_offset)
+            )
         }
-        InstructionType::Br(_, label_ref) => label_ref.content().to_owned(),
+        InstructionType::Br(_, label_or_offset) => print_label_or_immediate(label_or_offset),
         InstructionType::Jmp(register) | InstructionType::Jsrr(register) => {
             register.content().to_owned()
         }
-        InstructionType::Jsr(label_ref) => label_ref.content().to_owned(),
+        InstructionType::Jsr(label_or_offset) => print_label_or_immediate(label_or_offset),
         InstructionType::Nop
+        | InstructionType::Rti
         | InstructionType::Ret
         | InstructionType::Halt
         | InstructionType::Puts
@@ -364,6 +389,14 @@ fn print_register_or_immediate(either: &Either<Register, Immediate>) -> String {
     .to_owned()
 }
 
+fn print_label_or_immediate(either: &Either<LabelReference, Immediate>) -> String {
+    match either {
+        Either::Left(l) => l.content(),
+        Either::Right(im) => im.content(),
+    }
+    .to_owned()
+}
+
 fn print_directive(directive: &Directive) -> String {
     let operands: String = match directive.directive_type() {
         DirectiveType::ORIG(address) => address.content(),
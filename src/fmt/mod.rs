@@ -1,3 +1,5 @@
+mod config;
 mod formatter;
 
+pub use config::*;
 pub use formatter::*;
@@ -1,5 +1,12 @@
+pub mod asm;
 pub mod ast;
 pub mod bin_utils;
+pub mod config;
+pub mod db;
+pub mod diagnostic;
 pub mod error;
 pub mod fmt;
 pub mod lint;
+pub mod lsp;
+pub mod semantic;
+pub mod sim;
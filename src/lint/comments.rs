@@ -0,0 +1,304 @@
+use crate::ast::processed_ast::{LineColumn, Program, ProgramItem};
+use crate::ast::raw_ast::{Comment, Directive, DirectiveType, InstructionType, Label, Span};
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor, RuleLevel};
+use either::Either;
+use std::collections::HashSet;
+
+/// Longest line `line-length` allows when the configuration does not set one.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 80;
+
+// columns between tab stops, as the diagnostics are rendered
+const TAB_WIDTH: usize = 4;
+
+/// A comment block before the first `.ORIG`, describing the program.
+#[derive(Default)]
+pub struct FileHeader {
+    has_comment: bool,
+    // whether the first `.ORIG` was seen
+    done: bool,
+}
+
+/// A comment block right above every label `JSR` calls, naming the
+/// configured keys such as `Input:` or `Output:`.
+pub struct SubroutineDoc {
+    required_keys: Vec<String>,
+    errors: Vec<Error>,
+}
+
+/// Lines wider than the configured length, in characters with tabs expanded.
+pub struct LineLength {
+    max: usize,
+    errors: Vec<Error>,
+}
+
+impl FileHeader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProgramItemVisitor for FileHeader {
+    fn id(&self) -> &'static str {
+        "file-header"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Off
+    }
+
+    fn prepare(&mut self, _: &Program) {
+        *self = Self::default();
+    }
+
+    fn visit_comment(&mut self, _: &Comment, _: &LineColumn) -> Vec<Error> {
+        self.has_comment = true;
+        vec![]
+    }
+
+    fn visit_directive(
+        &mut self,
+        _: &[Label],
+        directive: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        if self.done || !matches!(directive.directive_type(), DirectiveType::ORIG(_)) {
+            return vec![];
+        }
+        self.done = true;
+        match self.has_comment {
+            true => vec![],
+            false => vec![Error::new(ErrorKind::MissingHeader, directive.span())],
+        }
+    }
+}
+
+impl SubroutineDoc {
+    pub fn new(required_keys: Vec<String>) -> Self {
+        Self {
+            required_keys,
+            errors: vec![],
+        }
+    }
+}
+
+impl ProgramItemVisitor for SubroutineDoc {
+    fn id(&self) -> &'static str {
+        "subroutine-doc"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Off
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.errors.clear();
+        let subroutines: HashSet<&str> = program
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                ProgramItem::Instruction(_, instruction, ..) => {
+                    match instruction.instruction_type() {
+                        InstructionType::Jsr(Either::Left(label)) => Some(label.content().as_str()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect();
+        // standalone comments since the last other item, with their lines
+        let mut block: Vec<(usize, &str)> = vec![];
+        for item in program.items() {
+            let labels = match item {
+                ProgramItem::Comment(comment, location) => {
+                    block.push((*location.line(), comment.content()));
+                    continue;
+                }
+                ProgramItem::Instruction(labels, ..)
+                | ProgramItem::Directive(labels, ..)
+                | ProgramItem::EOL(labels) => labels.as_slice(),
+                ProgramItem::Error(..) => &[],
+            };
+            for label in labels {
+                if !subroutines.contains(label.name()) {
+                    continue;
+                }
+                // the comments on the lines right above the label
+                let mut line = program.line_of(*label.span().start());
+                let mut doc = vec![];
+                for (comment_line, content) in block.iter().rev() {
+                    if *comment_line + 1 != line {
+                        break;
+                    }
+                    doc.push(*content);
+                    line = *comment_line;
+                }
+                let name = label.name().to_owned();
+                let kind = match doc.is_empty() {
+                    true => ErrorKind::MissingDoc(name),
+                    false => {
+                        let missing: Vec<String> = self
+                            .required_keys
+                            .iter()
+                            .filter(|key| !doc.iter().any(|content| content.contains(key.as_str())))
+                            .cloned()
+                            .collect();
+                        if missing.is_empty() {
+                            continue;
+                        }
+                        ErrorKind::MissingDocKeys(name, missing)
+                    }
+                };
+                self.errors.push(Error::new(kind, label.span()));
+            }
+            block.clear();
+        }
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+impl LineLength {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            errors: vec![],
+        }
+    }
+}
+
+impl ProgramItemVisitor for LineLength {
+    fn id(&self) -> &'static str {
+        "line-length"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Off
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.errors = program
+            .lines()
+            .filter_map(|(start, line)| {
+                let line = line.trim_end();
+                let mut width = 0;
+                // byte offset of the first character past the limit
+                let mut over = None;
+                for (index, c) in line.char_indices() {
+                    width = match c {
+                        '\t' => (width / TAB_WIDTH + 1) * TAB_WIDTH,
+                        _ => width + 1,
+                    };
+                    if width > self.max && over.is_none() {
+                        over = Some(index);
+                    }
+                }
+                over.map(|index| {
+                    Error::new(
+                        ErrorKind::LineTooLong(width, self.max),
+                        &Span::new(start + index, start + line.len()),
+                    )
+                })
+            })
+            .collect();
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{LineLength, RuleLevel, SubroutineDoc, lint_errors_with};
+
+    const RULES: [&str; 3] = ["file-header", "subroutine-doc", "line-length"];
+
+    fn comments(content: &str) -> Vec<String> {
+        lint_errors_with(content, |linter| {
+            linter.register(Box::new(SubroutineDoc::new(vec![
+                "Input:".to_string(),
+                "Output:".to_string(),
+            ])));
+            linter.register(Box::new(LineLength::new(30)));
+            for id in RULES {
+                linter.set_level(id, RuleLevel::Warn);
+            }
+        })
+        .iter()
+        .filter(|error| RULES.contains(error.rule()))
+        .map(|error| error.kind().to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_documented() {
+        let content = r#"; Prints a greeting
+.ORIG x3000
+JSR PRINT
+JSR NEWLINE
+HALT
+; Prints the greeting
+; Input: none
+; Output: none
+PRINT LEA R0, TEXT
+PUTS
+RET
+; Prints a line break
+; Output: none
+
+NEWLINE AND R0, R0, #0
+ADD R0, R0, #10
+OUT
+RET
+TEXT .STRINGZ "hi" ; the text, which is far too long for a line
+.END
+"#;
+        assert_eq!(
+            comments(content),
+            vec![
+                "Subroutine `NEWLINE` has no doc comment",
+                "Line is 63 characters long, more than 30",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_length() {
+        let content = ".ORIG x3000\n\
+            LEA R0, A_VERY_LONG_LABEL_NAME_HERE\n\
+            \t\t\t\t\t\t\tHALT\n\
+            ; ünïcödé cömmënt ïs thïrty ok\n\
+            A_VERY_LONG_LABEL_NAME_HERE .FILL #0\n\
+            .END\n";
+        assert_eq!(
+            comments(content),
+            vec![
+                "No header comment before `.ORIG`",
+                "Line is 35 characters long, more than 30",
+                "Line is 32 characters long, more than 30",
+                "Line is 36 characters long, more than 30",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undocumented() {
+        let content = r#".ORIG x3000
+JSR PRINT
+HALT
+; Output: none
+PRINT RET
+.END
+"#;
+        assert_eq!(
+            comments(content),
+            vec![
+                "No header comment before `.ORIG`",
+                "Doc comment of `PRINT` lacks `Input:`",
+            ]
+        );
+    }
+}
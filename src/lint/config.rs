@@ -0,0 +1,141 @@
+use crate::ast::processed_ast::Program;
+use crate::config::{ConfigError, read_config};
+use crate::lint::{
+    CaseStyle, DEFAULT_MAX_LINE_LENGTH, DEFAULT_STYLE, LineLength, LintStyle, Linter, RuleLevel,
+    SubroutineDoc, UNUSED_SUPPRESSION, UnusedLabel, default_rules,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const CONFIG_FILENAME: &str = "lc3-lint.toml";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ConfigLintStyle {
+    colon_after_label: Option<bool>,
+    label_style: Option<CaseStyle>,
+    instruction_style: Option<CaseStyle>,
+    directive_style: Option<CaseStyle>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ConfigComments {
+    // keys every subroutine doc comment has to mention
+    #[serde(default)]
+    required_keys: Vec<String>,
+    max_line_length: Option<usize>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Config {
+    // label the program starts at, never reported as unused
+    #[serde(default, rename = "entry-label")]
+    entry_label: Option<String>,
+    #[serde(default, rename = "lint-style")]
+    lint_style: ConfigLintStyle,
+    // rule id to level, rules not listed keep their default level
+    #[serde(default)]
+    rules: BTreeMap<String, RuleLevel>,
+    #[serde(default)]
+    comments: ConfigComments,
+}
+
+/// Settings read from an `lc3-lint.toml`, the defaults when there is none.
+#[derive(Clone)]
+pub struct LintConfig {
+    style: LintStyle,
+    levels: BTreeMap<String, RuleLevel>,
+    entry_label: Option<String>,
+    comments: ConfigComments,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            style: DEFAULT_STYLE,
+            levels: BTreeMap::new(),
+            entry_label: None,
+            comments: ConfigComments::default(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let config: Config = read_config(path)?;
+        Ok(Self {
+            style: config_lint_style_to_lint_style(&DEFAULT_STYLE, config.lint_style),
+            levels: config.rules,
+            entry_label: config.entry_label,
+            comments: config.comments,
+        })
+    }
+
+    pub fn style(&self) -> &LintStyle {
+        &self.style
+    }
+
+    /// Rule ids under `[rules]` that name no rule.
+    pub fn unknown_rules(&self) -> Vec<&str> {
+        let rules = default_rules(&self.style);
+        let ids: Vec<_> = rules.iter().map(|rule| rule.id()).collect();
+        self.levels
+            .keys()
+            .map(String::as_str)
+            .filter(|id| !ids.contains(id) && *id != UNUSED_SUPPRESSION)
+            .collect()
+    }
+
+    /// Linter with the built-in rules at the configured levels.
+    pub fn linter(&self, program: Program) -> Linter {
+        let mut linter = Linter::new(self.style, program);
+        linter.register(Box::new(UnusedLabel::new(self.entry_label.clone())));
+        let comments = &self.comments;
+        linter.register(Box::new(SubroutineDoc::new(comments.required_keys.clone())));
+        linter.register(Box::new(LineLength::new(
+            comments.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH),
+        )));
+        for (id, level) in &self.levels {
+            linter.set_level(id, *level);
+        }
+        linter
+    }
+}
+
+fn config_lint_style_to_lint_style(
+    default: &LintStyle,
+    config_lint_style: ConfigLintStyle,
+) -> LintStyle {
+    LintStyle {
+        colon_after_label: config_lint_style
+            .colon_after_label
+            .unwrap_or(default.colon_after_label),
+        label_style: config_lint_style.label_style.unwrap_or(default.label_style),
+        instruction_style: config_lint_style
+            .instruction_style
+            .unwrap_or(default.instruction_style),
+        directive_style: config_lint_style
+            .directive_style
+            .unwrap_or(default.directive_style),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rules_only() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test/data/lsp")
+            .join(CONFIG_FILENAME);
+        let config = match LintConfig::read(&path) {
+            Ok(config) => config,
+            Err(err) => panic!("{err}"),
+        };
+        assert_eq!(config.levels.get("uninitialized"), Some(&RuleLevel::Off));
+        assert_eq!(config.style().label_style, DEFAULT_STYLE.label_style);
+    }
+}
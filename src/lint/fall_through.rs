@@ -0,0 +1,104 @@
+use crate::ast::processed_ast::Program;
+use crate::lint::flow::FlowGraph;
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+
+/// Reachable instructions that let execution continue into a `.FILL`,
+/// `.BLKW` or `.STRINGZ`, which then runs as code.
+#[derive(Default)]
+pub struct DataFallThrough {
+    errors: Vec<Error>,
+}
+
+impl DataFallThrough {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProgramItemVisitor for DataFallThrough {
+    fn id(&self) -> &'static str {
+        "data-fall-through"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        let graph = FlowGraph::new(program);
+        self.errors = graph
+            .nodes()
+            .iter()
+            .zip(graph.reachable())
+            .filter(|(_, reachable)| *reachable)
+            .filter_map(|(node, _)| {
+                let directive = (*node.falls_into())?;
+                Some(
+                    Error::new(
+                        ErrorKind::DataFallThrough(directive.content().clone()),
+                        directive.span(),
+                    )
+                    .with_related(
+                        node.instruction().full_span(),
+                        "Execution continues past this instruction",
+                    ),
+                )
+            })
+            .collect();
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::lint_errors;
+
+    fn fall_through(content: &str) -> Vec<(&str, &str)> {
+        let text = |span: &crate::ast::raw_ast::Span| &content[*span.start()..*span.end()];
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "data-fall-through")
+            .map(|error| (text(error.span()), text(&error.related()[0].0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_fall_through() {
+        let content = r#".ORIG x3000
+AND R0, R0, #0
+BRz SKIP
+COUNT .FILL #3
+SKIP JSR SUB
+TEXT .STRINGZ "hi"
+SUB RET
+.END
+.ORIG x4000
+AND R0, R0, #0
+.BLKW #0
+.BLKW #2
+.END
+"#;
+        assert_eq!(
+            fall_through(content),
+            vec![
+                (".FILL", "BRz SKIP"),
+                (".STRINGZ", "JSR SUB"),
+                (".BLKW", "AND R0, R0, #0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_fall_through() {
+        let content = r#".ORIG x3000
+LD R0, COUNT
+BRnzp DONE
+COUNT .FILL #3
+DONE HALT
+TEXT .STRINGZ "hi"
+DEAD ADD R0, R0, #1
+DATA .FILL #1
+.END
+"#;
+        assert!(fall_through(content).is_empty());
+    }
+}
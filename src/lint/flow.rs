@@ -0,0 +1,195 @@
+use crate::asm::{directive_size, item_addresses};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{BrType, Directive, DirectiveType, Instruction, InstructionType};
+use either::Either;
+use getset::Getters;
+use std::collections::HashMap;
+
+/// An instruction and where execution may go after it.
+#[derive(Debug, Getters)]
+pub(crate) struct Node<'a> {
+    #[get = "pub(crate)"]
+    instruction: &'a Instruction,
+    #[get = "pub(crate)"]
+    address: u16,
+    // nodes executed next, a call counts as returning to the next instruction
+    #[get = "pub(crate)"]
+    successors: Vec<usize>,
+    // entry of the subroutine a `JSR` calls
+    #[get = "pub(crate)"]
+    call: Option<usize>,
+    // data right after the instruction, run as code if execution falls through
+    #[get = "pub(crate)"]
+    falls_into: Option<&'a Directive>,
+    // data a branch or `JSR` targets, run as code when the transfer is taken
+    #[get = "pub(crate)"]
+    jumps_into: Option<&'a Directive>,
+}
+
+/// Control-flow graph of the instructions that have an address, one node per
+/// instruction in source order. Targets in registers (`JMP`, `JSRR`) are unknown
+/// and have no edge.
+#[derive(Debug)]
+pub(crate) struct FlowGraph<'a> {
+    nodes: Vec<Node<'a>>,
+    // node of the instruction at every address
+    at: HashMap<u16, usize>,
+    // data directive at every address it starts at
+    data: HashMap<u16, &'a Directive>,
+    // address of every label, the first definition wins
+    labels: HashMap<&'a str, u16>,
+    // node at the origin of every block that starts with an instruction
+    entries: Vec<usize>,
+}
+
+impl<'a> FlowGraph<'a> {
+    pub(crate) fn new(program: &'a Program) -> Self {
+        let mut graph = Self {
+            nodes: vec![],
+            at: HashMap::new(),
+            data: HashMap::new(),
+            labels: HashMap::new(),
+            entries: vec![],
+        };
+        let mut origins = vec![];
+        for (item, address) in program.items().iter().zip(item_addresses(program)) {
+            let Some(address) = address else {
+                continue;
+            };
+            let labels = match item {
+                ProgramItem::Instruction(labels, instruction, ..) => {
+                    graph.at.entry(address).or_insert(graph.nodes.len());
+                    graph.nodes.push(Node {
+                        instruction,
+                        address,
+                        successors: vec![],
+                        call: None,
+                        falls_into: None,
+                        jumps_into: None,
+                    });
+                    labels
+                }
+                ProgramItem::Directive(labels, directive, ..) => {
+                    match directive.directive_type() {
+                        DirectiveType::ORIG(origin) => origins.push(*origin.value()),
+                        DirectiveType::END => {}
+                        _ if directive_size(directive) > 0 => {
+                            graph.data.entry(address).or_insert(directive);
+                        }
+                        _ => {}
+                    }
+                    labels
+                }
+                ProgramItem::EOL(labels) => labels,
+                ProgramItem::Comment(..) | ProgramItem::Error(..) => continue,
+            };
+            for label in labels {
+                graph.labels.entry(label.name()).or_insert(address);
+            }
+        }
+        for index in 0..graph.nodes.len() {
+            let (successors, call) = graph.edges(&graph.nodes[index]);
+            graph.nodes[index].successors = successors;
+            graph.nodes[index].call = call;
+            let node = &graph.nodes[index];
+            if let InstructionType::Br(..) | InstructionType::Jsr(_) =
+                node.instruction.instruction_type()
+            {
+                graph.nodes[index].jumps_into = graph
+                    .target(node)
+                    .filter(|address| !graph.at.contains_key(address))
+                    .and_then(|address| graph.data.get(&address).copied());
+            }
+            let node = &graph.nodes[index];
+            let next = node.address.wrapping_add(1);
+            if !node.instruction.is_unconditional_transfer() && !graph.at.contains_key(&next) {
+                graph.nodes[index].falls_into = graph.data.get(&next).copied();
+            }
+        }
+        graph.entries = origins
+            .iter()
+            .filter_map(|origin| graph.at.get(origin).copied())
+            .collect();
+        graph
+    }
+
+    pub(crate) fn nodes(&self) -> &[Node<'a>] {
+        &self.nodes
+    }
+
+    /// Nodes execution starts at when a block is loaded and run.
+    pub(crate) fn entries(&self) -> &[usize] {
+        &self.entries
+    }
+
+    /// Whether each node runs when the blocks are run from their origin,
+    /// calls included.
+    pub(crate) fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut pending = self.entries.clone();
+        while let Some(index) = pending.pop() {
+            if std::mem::replace(&mut reachable[index], true) {
+                continue;
+            }
+            let node = &self.nodes[index];
+            pending.extend(node.successors.iter().chain(&node.call));
+        }
+        reachable
+    }
+
+    /// Entry node of every subroutine called by label, with its name, in call order.
+    pub(crate) fn subroutines(&self) -> Vec<(&'a str, usize)> {
+        let mut subroutines: Vec<(&str, usize)> = vec![];
+        for node in &self.nodes {
+            let (InstructionType::Jsr(Either::Left(label)), Some(entry)) =
+                (node.instruction.instruction_type(), node.call)
+            else {
+                continue;
+            };
+            if !subroutines.iter().any(|(_, other)| *other == entry) {
+                subroutines.push((label.content(), entry));
+            }
+        }
+        subroutines
+    }
+
+    /// Node a branch or `JSR` transfers control to, by label or by offset.
+    pub(crate) fn jump_target(&self, node: &Node) -> Option<usize> {
+        match node.instruction.instruction_type() {
+            InstructionType::Br(..) | InstructionType::Jsr(_) => self
+                .target(node)
+                .and_then(|address| self.at.get(&address).copied()),
+            _ => None,
+        }
+    }
+
+    // address a PC-relative operand refers to, by label or by offset
+    fn target(&self, node: &Node) -> Option<u16> {
+        node.instruction
+            .pc_offset()
+            .and_then(|operand| match operand {
+                Either::Left(label) => self.labels.get(label.content().as_str()).copied(),
+                Either::Right(offset) => Some(
+                    node.address
+                        .wrapping_add(1)
+                        .wrapping_add(*offset.value() as u16),
+                ),
+            })
+    }
+
+    fn edges(&self, node: &Node) -> (Vec<usize>, Option<usize>) {
+        let next = self.at.get(&node.address.wrapping_add(1)).copied();
+        let target = self
+            .target(node)
+            .and_then(|address| self.at.get(&address).copied());
+        match node.instruction.instruction_type() {
+            InstructionType::Br(BrType::Nzp | BrType::None, _) => {
+                (target.into_iter().collect(), None)
+            }
+            InstructionType::Br(..) => (next.into_iter().chain(target).collect(), None),
+            InstructionType::Jsr(_) => (next.into_iter().collect(), target),
+            _ if node.instruction.is_unconditional_transfer() => (vec![], None),
+            _ => (next.into_iter().collect(), None),
+        }
+    }
+}
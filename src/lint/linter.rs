@@ -1,9 +1,15 @@
 use crate::ast::processed_ast::{LineColumn, Program, ProgramItem};
 use crate::ast::raw_ast::{Comment, Directive, Instruction, Label, Span};
+use crate::diagnostic::{Diagnostic, Fix, Severity};
+use crate::lint::suppression::Suppressions;
+use crate::lint::{
+    DEFAULT_MAX_LINE_LENGTH, DataFallThrough, DirectiveCase, FileHeader, InstructionCase,
+    LabelCase, LabelColon, LineLength, ProgramStructure, ReservedOpcode, ReturnAddress,
+    SubroutineDoc, UNUSED_SUPPRESSION, Uninitialized, UnreachableCode, UnusedLabel,
+};
 use getset::Getters;
-use once_cell::sync::Lazy;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(PartialOrd, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum CaseStyle {
@@ -22,261 +28,588 @@ pub struct LintStyle {
     pub directive_style: CaseStyle,
 }
 
-#[derive(Debug, Getters)]
-pub struct Error {
-    #[get = "pub"]
-    case_style_error: Result<(), (CaseStyle, Option<CaseStyle>)>,
-    #[get = "pub"]
-    colon_style_error: Result<(), ()>,
-    #[get = "pub"]
-    span: Span,
-}
-
-pub struct Linter {
-    program: Program,
-    visitor: Box<dyn ProgramItemVisitor>,
-}
-
-impl Linter {
-    pub fn new(style: LintStyle, program: Program) -> Self {
-        Self {
-            program,
-            visitor: Box::new(StyleCheckerVisitor { style }),
+impl CaseStyle {
+    /// Respells an identifier in this style, splitting words at `_` and at case changes.
+    pub fn convert(self, identifier: &str) -> String {
+        let words = split_words(identifier);
+        match self {
+            CaseStyle::LowerCamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| match index {
+                    0 => word.to_lowercase(),
+                    _ => capitalize(word),
+                })
+                .collect(),
+            CaseStyle::UpperCamelCase => words.iter().map(|word| capitalize(word)).collect(),
+            CaseStyle::SnakeCase => words.join("_").to_lowercase(),
+            CaseStyle::ScreamingSnakeCase => words.join("_").to_uppercase(),
         }
     }
 
-    pub fn check(&mut self) -> Result<(), Vec<Error>> {
-        self.accept()
+    /// Respells a mnemonic, which is always a single word.
+    pub fn convert_keyword(self, keyword: &str) -> String {
+        match self {
+            CaseStyle::LowerCamelCase | CaseStyle::SnakeCase => keyword.to_lowercase(),
+            CaseStyle::UpperCamelCase => capitalize(keyword),
+            CaseStyle::ScreamingSnakeCase => keyword.to_uppercase(),
+        }
     }
+}
 
-    fn accept(&mut self) -> Result<(), Vec<Error>> {
-        let mut errors = vec![];
-        for line in self.program.items() {
-            let mut res = match line {
-                ProgramItem::Comment(comment, lc) => self.visitor.visit_comment(comment, lc),
-                ProgramItem::Instruction(labels, instruction, comment, lc) => self
-                    .visitor
-                    .visit_instruction(labels, instruction, comment, lc),
-                ProgramItem::Directive(labels, directive, comment, lc) => {
-                    self.visitor.visit_directive(labels, directive, comment, lc)
+fn split_words(identifier: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = identifier.char_indices().collect();
+    let mut words = vec![];
+    let mut start = 0;
+    for (index, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if start < offset {
+                words.push(&identifier[start..offset]);
+            }
+            start = offset + 1;
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|index| chars[index].1);
+        let next = chars.get(index + 1).map(|(_, c)| *c);
+        // `loopStart`, `loop2Start` and the `S` of `LOOPStart` begin a word
+        let boundary = c.is_uppercase()
+            && match previous {
+                Some(previous) => {
+                    previous.is_lowercase()
+                        || previous.is_ascii_digit()
+                        || (previous.is_uppercase() && next.is_some_and(char::is_lowercase))
                 }
-                ProgramItem::EOL(labels) => self.visitor.visit_eol(labels),
+                None => false,
             };
-            errors.append(&mut res);
+        if boundary && start < offset {
+            words.push(&identifier[start..offset]);
+            start = offset;
         }
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+    }
+    if start < identifier.len() {
+        words.push(&identifier[start..]);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+    }
+}
+
+/// Style used when no `lc3-lint.toml` is found.
+pub const DEFAULT_STYLE: LintStyle = LintStyle {
+    colon_after_label: false,
+    label_style: CaseStyle::ScreamingSnakeCase,
+    instruction_style: CaseStyle::ScreamingSnakeCase,
+    directive_style: CaseStyle::ScreamingSnakeCase,
+};
+
+/// How the problems of a rule are reported, set per rule id in `lc3-lint.toml`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warn,
+    Error,
+}
+
+impl RuleLevel {
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Warn => Some(Severity::Warning),
+            RuleLevel::Error => Some(Severity::Error),
         }
     }
 }
 
-trait ProgramItemVisitor {
-    fn visit_comment(&mut self, comment: &Comment, location: &LineColumn) -> Vec<Error>;
-    fn visit_instruction(
-        &mut self,
-        labels: &[Label],
-        instruction: &Instruction,
-        comment: &Option<Comment>,
-        location: &LineColumn,
-    ) -> Vec<Error>;
-    fn visit_directive(
-        &mut self,
-        labels: &[Label],
-        directive: &Directive,
-        comment: &Option<Comment>,
-        location: &LineColumn,
-    ) -> Vec<Error>;
-    fn visit_eol(&mut self, labels: &[Label]) -> Vec<Error>;
+/// Part of a line a style error is about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleTarget {
+    Label,
+    Instruction,
+    Directive,
 }
 
-struct StyleCheckerVisitor {
-    style: LintStyle,
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    // expected style, and the style found if it is a known one
+    CaseStyle(StyleTarget, CaseStyle, Option<CaseStyle>),
+    // whether the label should end with a colon
+    ColonStyle(bool),
+    UnusedSuppression,
+    // instructions after the named unconditional transfer
+    UnreachableCode(String),
+    // `.ORIG` whose block is never closed
+    MissingEnd,
+    // `.END` with no open block
+    UnmatchedEnd,
+    // items outside of any block, whether they come after an `.END`
+    OutsideBlock(bool),
+    // origin of the earlier block an `.ORIG` block overlaps
+    OverlappingBlock(u16),
+    // subroutine, the call overwriting R7 and the line of every instruction from
+    // the subroutine's entry to the `RET`
+    ClobberedReturn(String, String, Vec<usize>),
+    // register read before any write on some path
+    UninitializedRegister(String),
+    // conditional branch before any instruction set the condition codes
+    UnsetConditionCodes(String),
+    // data directive execution falls through into
+    DataFallThrough(String),
+    // `.FILL` of a word with the reserved opcode that runs as code
+    ReservedOpcode(String),
+    UnusedLabel(String),
+    // label and directive of data nothing loads or stores
+    UnreferencedData(String, String),
+    MissingHeader,
+    MissingDoc(String),
+    // subroutine and the required keys its doc comment lacks
+    MissingDocKeys(String, Vec<String>),
+    // length of the line and the longest allowed
+    LineTooLong(usize, usize),
+    // reported by a rule registered from outside this crate, with its code
+    Custom(&'static str, String),
 }
 
-static LOWER_CAMEL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]+(?:[A-Z][a-z0-9]*)*$").unwrap());
-static UPPER_CAMEL: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^[A-Z][a-z0-9]*(?:[A-Z][a-z0-9]*)*$").unwrap());
-static SNAKE_CASE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]+(?:_[a-z0-9]+)*$").unwrap());
-static SCREAMING_SNAKE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^[A-Z0-9]+(?:_[A-Z0-9]+)*$").unwrap());
-
-impl StyleCheckerVisitor {
-    fn check_label(&self, label: &str) -> (Result<(), Option<CaseStyle>>, Result<(), ()>) {
-        let case_error = Self::check_keyword_style(
-            label.strip_suffix(":").unwrap_or(label),
-            &self.style.label_style,
-        );
-        let colon_error = match label.ends_with(":") {
-            true => {
-                if self.style.colon_after_label {
-                    Ok(())
-                } else {
-                    Err(())
-                }
-            }
-            false => {
-                if self.style.colon_after_label {
-                    Err(())
-                } else {
-                    Ok(())
-                }
-            }
-        };
-        (case_error, colon_error)
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::CaseStyle(StyleTarget::Label, ..) => "L001",
+            ErrorKind::ColonStyle(_) => "L002",
+            ErrorKind::CaseStyle(StyleTarget::Instruction, ..) => "L003",
+            ErrorKind::CaseStyle(StyleTarget::Directive, ..) => "L004",
+            ErrorKind::UnusedSuppression => "L005",
+            ErrorKind::UnreachableCode(_) => "L006",
+            ErrorKind::MissingEnd
+            | ErrorKind::UnmatchedEnd
+            | ErrorKind::OutsideBlock(_)
+            | ErrorKind::OverlappingBlock(_) => "L007",
+            ErrorKind::ClobberedReturn(..) => "L008",
+            ErrorKind::UninitializedRegister(_) | ErrorKind::UnsetConditionCodes(_) => "L009",
+            ErrorKind::DataFallThrough(_) => "L010",
+            ErrorKind::UnusedLabel(_) | ErrorKind::UnreferencedData(..) => "L011",
+            ErrorKind::MissingHeader => "L012",
+            ErrorKind::MissingDoc(_) | ErrorKind::MissingDocKeys(..) => "L013",
+            ErrorKind::LineTooLong(..) => "L014",
+            ErrorKind::ReservedOpcode(_) => "L015",
+            ErrorKind::Custom(code, _) => code,
+        }
     }
+}
 
-    fn check_instruction(&self, instruction: &str) -> Result<(), Option<CaseStyle>> {
-        Self::check_keyword_style(instruction, &self.style.instruction_style)
+#[derive(Debug, Getters)]
+pub struct Error {
+    #[get = "pub"]
+    kind: ErrorKind,
+    #[get = "pub"]
+    span: Span,
+    #[get = "pub"]
+    fix: Option<Fix>,
+    // other places the error is about, with what happens there
+    #[get = "pub"]
+    related: Vec<(Span, String)>,
+    // id and level of the reporting rule, filled in by the linter
+    #[get = "pub"]
+    rule: &'static str,
+    #[get = "pub"]
+    severity: Severity,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: &Span) -> Self {
+        Self {
+            kind,
+            span: span.clone(),
+            fix: None,
+            related: vec![],
+            rule: "",
+            severity: Severity::Warning,
+        }
     }
 
-    fn check_directive_style(&self, directive: &str) -> Result<(), Option<CaseStyle>> {
-        Self::check_keyword_style(directive, &self.style.directive_style)
+    pub fn with_fix(mut self, fix: Option<Fix>) -> Self {
+        self.fix = fix;
+        self
     }
 
-    #[allow(clippy::if_same_then_else)]
-    fn check_keyword_style(keyword: &str, case_style: &CaseStyle) -> Result<(), Option<CaseStyle>> {
-        let found_style = match Self::get_identifier_style(keyword) {
-            None => {
-                return Err(None);
-            }
-            Some(st) => st,
-        };
-        if &found_style == case_style {
-            Ok(())
-        }
-        // snakecase is a subset of a lower camelcase without _
-        else if found_style == CaseStyle::SnakeCase
-            && (!keyword.contains("_"))
-            && *case_style == CaseStyle::LowerCamelCase
-        {
-            Ok(())
-        } else {
-            Err(Some(found_style))
-        }
+    pub fn with_related(mut self, span: &Span, message: impl ToString) -> Self {
+        self.related.push((span.clone(), message.to_string()));
+        self
     }
-    fn get_identifier_style(identifier: &str) -> Option<CaseStyle> {
-        if SNAKE_CASE.is_match(identifier) {
-            Some(CaseStyle::SnakeCase)
-        } else if SCREAMING_SNAKE.is_match(identifier) {
-            Some(CaseStyle::ScreamingSnakeCase)
-        } else if LOWER_CAMEL.is_match(identifier) {
-            Some(CaseStyle::LowerCamelCase)
-        } else if UPPER_CAMEL.is_match(identifier) {
-            Some(CaseStyle::UpperCamelCase)
-        } else {
-            None
-        }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
     }
 
-    fn label_error_to_error(
-        label: &Label,
-        expected_case: &CaseStyle,
-        case_error: Result<(), Option<CaseStyle>>,
-        colon_error: Result<(), ()>,
-    ) -> Error {
-        Error {
-            case_style_error: case_error.map_err(|e| (*expected_case, e)),
-            colon_style_error: colon_error,
-            span: label.span().clone(),
+    fn reported_by(self, rule: &'static str, severity: Severity) -> Self {
+        Self {
+            rule,
+            severity,
+            ..self
         }
     }
+}
 
-    fn check_label_style(&self, labels: &[Label]) -> Vec<Error> {
-        let mut errors = vec![];
-        for label in labels {
-            let (case_error, colon_error) = self.check_label(label.content());
-            if case_error.is_err() || colon_error.is_err() {
-                errors.push(Self::label_error_to_error(
-                    label,
-                    &self.style.label_style,
-                    case_error,
-                    colon_error,
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let diagnostic = Diagnostic::new(
+            error.code(),
+            error.severity,
+            error.kind.to_string(),
+            error.span(),
+        )
+        .with_label(match (&error.kind, error.severity) {
+            (ErrorKind::UnreachableCode(_), _) => "No label leads here",
+            (ErrorKind::ClobberedReturn(..), _) => "Overwrites the return address",
+            (ErrorKind::DataFallThrough(_), _) => "Runs as an instruction",
+            (ErrorKind::ReservedOpcode(_), _) => "Raises an illegal opcode exception",
+            (_, Severity::Warning) => "Warning occurred here",
+            (_, Severity::Error) => "Error occurred here",
+        });
+        let diagnostic = match &error.kind {
+            ErrorKind::CaseStyle(..) | ErrorKind::ColonStyle(_) => diagnostic
+                .with_note("See the style guide for more information on formatting rules."),
+            ErrorKind::UnusedSuppression => {
+                diagnostic.with_note("Nothing it applies to is reported, so it can be removed.")
+            }
+            ErrorKind::UnreachableCode(_) => diagnostic
+                .with_note("Remove the instructions, or label them if another part jumps here."),
+            ErrorKind::MissingEnd | ErrorKind::UnmatchedEnd => {
+                diagnostic.with_note("Every `.ORIG` starts a block that an `.END` closes.")
+            }
+            ErrorKind::OutsideBlock(_) => diagnostic
+                .with_note("Only what is between `.ORIG` and `.END` has an address to load at."),
+            ErrorKind::OverlappingBlock(_) => diagnostic
+                .with_note("Loading both blocks writes the same memory twice, so one is lost."),
+            ErrorKind::ClobberedReturn(_, _, path) => diagnostic
+                .with_note(format!(
+                    "Path: lines {}",
+                    path.iter()
+                        .map(usize::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
                 ))
+                .with_note("Save R7 before the call and restore it before returning."),
+            ErrorKind::UninitializedRegister(_) => diagnostic
+                .with_note("Registers hold whatever was left in them when the program starts."),
+            ErrorKind::UnsetConditionCodes(_) => diagnostic
+                .with_note("Only ADD, AND, NOT, LD, LDI, LDR and LEA set the condition codes."),
+            ErrorKind::DataFallThrough(_) => diagnostic.with_note(
+                "End the code before it with HALT, RET or BRnzp, or move the data out of the way.",
+            ),
+            ErrorKind::ReservedOpcode(_) => diagnostic.with_note(
+                "Opcode 1101 has no instruction, running it traps to the handler at x0101.",
+            ),
+            ErrorKind::UnusedLabel(_) => diagnostic
+                .with_note("Remove the label, or set it as `entry-label` if it is the entry."),
+            ErrorKind::UnreferencedData(..) => diagnostic.with_note(
+                "No LD, LDI, LEA, ST or STI refers to it, so the program never uses it.",
+            ),
+            ErrorKind::MissingHeader => {
+                diagnostic.with_note("Describe the program in comments above `.ORIG`.")
             }
+            ErrorKind::MissingDoc(_) | ErrorKind::MissingDocKeys(..) => diagnostic.with_note(
+                "Describe the inputs, outputs and clobbered registers in comments right above the label.",
+            ),
+            ErrorKind::LineTooLong(..) => diagnostic,
+            ErrorKind::Custom(..) => diagnostic,
+        };
+        let diagnostic = match error.rule {
+            "" => diagnostic,
+            rule => diagnostic.with_rule(rule),
+        };
+        let diagnostic = error
+            .related
+            .iter()
+            .fold(diagnostic, |diagnostic, (span, message)| {
+                diagnostic.with_secondary(span, message)
+            });
+        match &error.fix {
+            None => diagnostic,
+            Some(fix) => diagnostic.with_fix(fix.clone()),
         }
-        errors
     }
 }
 
-impl ProgramItemVisitor for StyleCheckerVisitor {
-    fn visit_comment(&mut self, _: &Comment, _: &LineColumn) -> Vec<Error> {
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::CaseStyle(_, expected, found) => match found {
+                Some(found_style) => write!(
+                    f,
+                    "Invalid case style: found {:?}, expected {:?}",
+                    found_style, expected
+                ),
+                None => write!(f, "Unknown case style, expected {:?}", expected),
+            },
+            ErrorKind::ColonStyle(true) => write!(f, "Invalid colon style, expected a colon"),
+            ErrorKind::ColonStyle(false) => write!(f, "Invalid colon style, expected no colon"),
+            ErrorKind::UnusedSuppression => write!(f, "Unused suppression comment"),
+            ErrorKind::UnreachableCode(transfer) => {
+                write!(f, "Unreachable code after `{transfer}`")
+            }
+            ErrorKind::MissingEnd => write!(f, "`.ORIG` without a matching `.END`"),
+            ErrorKind::UnmatchedEnd => write!(f, "`.END` without a matching `.ORIG`"),
+            ErrorKind::OutsideBlock(true) => write!(f, "Code after `.END` is outside of any block"),
+            ErrorKind::OutsideBlock(false) => {
+                write!(f, "Code before `.ORIG` is outside of any block")
+            }
+            ErrorKind::OverlappingBlock(origin) => {
+                write!(f, "Block overlaps the block starting at x{origin:04X}")
+            }
+            ErrorKind::ClobberedReturn(subroutine, call, _) => {
+                write!(f, "`{call}` overwrites R7 before `{subroutine}` returns")
+            }
+            ErrorKind::UninitializedRegister(register) => {
+                write!(f, "{register} may be read before it is written")
+            }
+            ErrorKind::UnsetConditionCodes(branch) => write!(
+                f,
+                "`{branch}` may test condition codes no instruction has set"
+            ),
+            ErrorKind::DataFallThrough(directive) => {
+                write!(f, "Execution falls through into `{directive}` data")
+            }
+            ErrorKind::ReservedOpcode(directive) => {
+                write!(f, "`{directive}` runs as the reserved opcode 1101")
+            }
+            ErrorKind::UnusedLabel(label) => write!(f, "Label `{label}` is never used"),
+            ErrorKind::UnreferencedData(label, directive) => {
+                write!(f, "`{directive}` data `{label}` is never loaded or stored")
+            }
+            ErrorKind::MissingHeader => write!(f, "No header comment before `.ORIG`"),
+            ErrorKind::MissingDoc(subroutine) => {
+                write!(f, "Subroutine `{subroutine}` has no doc comment")
+            }
+            ErrorKind::MissingDocKeys(subroutine, keys) => write!(
+                f,
+                "Doc comment of `{subroutine}` lacks {}",
+                keys.iter()
+                    .map(|key| format!("`{key}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ErrorKind::LineTooLong(length, max) => {
+                write!(f, "Line is {length} characters long, more than {max}")
+            }
+            ErrorKind::Custom(_, message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// A lint rule. Before the program is visited in source order, `prepare`
+/// gets to look at all of it; what the visit methods return is reported
+/// under the rule's id.
+pub trait ProgramItemVisitor {
+    /// Name used to configure the rule in `lc3-lint.toml`.
+    fn id(&self) -> &'static str;
+
+    /// Level used when the configuration does not set one.
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Warn
+    }
+
+    fn prepare(&mut self, _program: &Program) {}
+
+    fn visit_comment(&mut self, _comment: &Comment, _location: &LineColumn) -> Vec<Error> {
         vec![]
     }
 
     fn visit_instruction(
         &mut self,
-        labels: &[Label],
-        instruction: &Instruction,
-        comment: &Option<Comment>,
-        lc: &LineColumn,
+        _labels: &[Label],
+        _instruction: &Instruction,
+        _comment: &Option<Comment>,
+        _location: &LineColumn,
     ) -> Vec<Error> {
-        let mut errors = vec![];
-        errors.append(&mut self.check_label_style(labels));
-        match self.check_instruction(instruction.content()) {
-            Ok(_) => {}
-            Err(err) => errors.push(Error {
-                case_style_error: Err((self.style.instruction_style, err)),
-                colon_style_error: Ok(()),
-                span: instruction.span().clone(),
-            }),
-        }
-        match comment {
-            None => {}
-            Some(comment) => {
-                errors.append(&mut self.visit_comment(comment, lc));
-            }
-        }
-        errors
+        vec![]
     }
 
     fn visit_directive(
         &mut self,
-        labels: &[Label],
-        directive: &Directive,
-        comment: &Option<Comment>,
-        lc: &LineColumn,
+        _labels: &[Label],
+        _directive: &Directive,
+        _comment: &Option<Comment>,
+        _location: &LineColumn,
     ) -> Vec<Error> {
-        let mut errors = vec![];
-        errors.append(&mut self.check_label_style(labels));
-        assert!(directive.content().starts_with("."));
-        match self.check_directive_style(directive.content().strip_prefix(".").unwrap()) {
-            Ok(_) => {}
-            Err(error) => {
-                errors.push(Error {
-                    case_style_error: Err((self.style.directive_style, error)),
-                    colon_style_error: Ok(()),
-                    span: directive.span().clone(),
-                });
-            }
+        vec![]
+    }
+
+    fn visit_eol(&mut self, _labels: &[Label]) -> Vec<Error> {
+        vec![]
+    }
+
+    /// Called after the last item, for problems only known at the end.
+    fn finish(&mut self) -> Vec<Error> {
+        vec![]
+    }
+}
+
+/// Every built-in rule, checking against the given style.
+pub fn default_rules(style: &LintStyle) -> Vec<Box<dyn ProgramItemVisitor>> {
+    vec![
+        Box::new(LabelCase::new(style.label_style)),
+        Box::new(LabelColon::new(style.colon_after_label)),
+        Box::new(InstructionCase::new(style.instruction_style)),
+        Box::new(DirectiveCase::new(style.directive_style)),
+        Box::new(UnreachableCode::new()),
+        Box::new(ProgramStructure::new()),
+        Box::new(ReturnAddress::new()),
+        Box::new(Uninitialized::new()),
+        Box::new(DataFallThrough::new()),
+        Box::new(ReservedOpcode::new()),
+        Box::new(UnusedLabel::new(None)),
+        Box::new(FileHeader::new()),
+        Box::new(SubroutineDoc::new(vec![])),
+        Box::new(LineLength::new(DEFAULT_MAX_LINE_LENGTH)),
+    ]
+}
+
+pub struct Linter {
+    program: Program,
+    rules: Vec<(Box<dyn ProgramItemVisitor>, RuleLevel)>,
+    unused_suppression: RuleLevel,
+}
+
+impl Linter {
+    /// Linter running every built-in rule at its default level.
+    pub fn new(style: LintStyle, program: Program) -> Self {
+        let mut linter = Self {
+            program,
+            rules: vec![],
+            unused_suppression: RuleLevel::Warn,
+        };
+        for rule in default_rules(&style) {
+            linter.register(rule);
         }
-        match comment {
-            None => {}
-            Some(comment) => {
-                errors.append(&mut self.visit_comment(comment, lc));
+        linter
+    }
+
+    /// Adds a rule at its default level, replacing a rule with the same id.
+    pub fn register(&mut self, rule: Box<dyn ProgramItemVisitor>) {
+        let level = rule.default_level();
+        self.rules.retain(|(other, _)| other.id() != rule.id());
+        self.rules.push((rule, level));
+    }
+
+    /// Returns false if no rule with that id is registered.
+    pub fn set_level(&mut self, id: &str, level: RuleLevel) -> bool {
+        if id == UNUSED_SUPPRESSION {
+            self.unused_suppression = level;
+            return true;
+        }
+        match self.rules.iter_mut().find(|(rule, _)| rule.id() == id) {
+            Some((_, rule_level)) => {
+                *rule_level = level;
+                true
             }
+            None => false,
         }
-        errors
     }
 
-    fn visit_eol(&mut self, labels: &[Label]) -> Vec<Error> {
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        let ids = self.rules.iter().map(|(rule, _)| rule.id());
+        ids.chain([UNUSED_SUPPRESSION]).collect()
+    }
+
+    pub fn check(&mut self) -> Result<(), Vec<Error>> {
+        self.accept()
+    }
+
+    fn accept(&mut self) -> Result<(), Vec<Error>> {
         let mut errors = vec![];
-        errors.append(&mut self.check_label_style(labels));
-        errors
+        let mut rules: Vec<_> = self
+            .rules
+            .iter_mut()
+            .filter_map(|(rule, level)| Some((rule, level.severity()?)))
+            .collect();
+        for (rule, _) in rules.iter_mut() {
+            rule.prepare(&self.program);
+        }
+        for line in self.program.items() {
+            for (rule, severity) in rules.iter_mut() {
+                let res = match line {
+                    ProgramItem::Comment(comment, lc) => rule.visit_comment(comment, lc),
+                    ProgramItem::Instruction(labels, instruction, comment, lc) => {
+                        rule.visit_instruction(labels, instruction, comment, lc)
+                    }
+                    ProgramItem::Directive(labels, directive, comment, lc) => {
+                        rule.visit_directive(labels, directive, comment, lc)
+                    }
+                    ProgramItem::EOL(labels) => rule.visit_eol(labels),
+                    ProgramItem::Error(..) => vec![],
+                };
+                let id = rule.id();
+                errors.extend(res.into_iter().map(|e| e.reported_by(id, *severity)));
+            }
+        }
+        for (rule, severity) in rules.iter_mut() {
+            let id = rule.id();
+            errors.extend(
+                rule.finish()
+                    .into_iter()
+                    .map(|e| e.reported_by(id, *severity)),
+            );
+        }
+        let mut suppressions = Suppressions::new(&self.program);
+        errors.retain(|error| {
+            let line = self.program.line_of(*error.span().start());
+            !suppressions.suppresses(error, line)
+        });
+        if let Some(severity) = self.unused_suppression.severity() {
+            let unused = suppressions.unused().into_iter();
+            errors.extend(unused.map(|e| e.reported_by(UNUSED_SUPPRESSION, severity)));
+        }
+        errors.sort_by_key(|error| *error.span().start());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 }
 
+/// Problems the built-in rules find in `content`, which must parse.
+#[cfg(test)]
+pub(crate) fn lint_errors(content: &str) -> Vec<Error> {
+    lint_errors_with(content, |_| {})
+}
+
+/// Problems found in `content` once `configure` has set up the linter.
+#[cfg(test)]
+pub(crate) fn lint_errors_with(content: &str, configure: impl FnOnce(&mut Linter)) -> Vec<Error> {
+    let mut linter = Linter::new(DEFAULT_STYLE, crate::ast::get_ast(content).unwrap());
+    configure(&mut linter);
+    linter.check().err().unwrap_or_default()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::ast::get_ast;
+    use crate::ast::raw_ast::InstructionType;
+    use crate::diagnostic::apply_fixes;
+
+    // the style snippets are not whole programs, so their structure is not checked
+    fn style_linter(style: LintStyle, program: Program) -> Linter {
+        let mut linter = Linter::new(style, program);
+        linter.set_level("program-structure", RuleLevel::Off);
+        linter
+    }
 
     fn test_true(style: LintStyle, content: &str) {
         let ast = get_ast(content);
         assert!(ast.is_ok());
         if let Ok(program) = ast {
-            let c = Linter::new(style, program).check();
+            let c = style_linter(style, program).check();
             if c.is_err() {
                 println!("{:?}", c.as_ref().err().unwrap());
             }
@@ -288,7 +621,7 @@ mod test {
         let ast = get_ast(content);
         assert!(ast.is_ok());
         if let Ok(program) = ast {
-            let c = Linter::new(style, program).check();
+            let c = style_linter(style, program).check();
             assert!(c.is_err());
         }
     }
@@ -381,14 +714,18 @@ mod test {
 
         let content_true1 = r#"AND R1, R2, R3"#;
         let content_true2 = r#"AND R4, R5, R6"#;
+        let content_true3 = r#"RTI"#;
         test_true(style, content_true1);
         test_true(style, content_true2);
+        test_true(style, content_true3);
 
         // Negation assertions: should fail for incorrect styles
         let content_false1 = r#"aND R1, R2, R3"#; // LowerCamelCase
         let content_false2 = r#"AnD R1, R2, R3"#; // SnakeCase
+        let content_false3 = r#"rti"#; // lowercase
         test_false(style, content_false1);
         test_false(style, content_false2);
+        test_false(style, content_false3);
     }
 
     #[test]
@@ -575,4 +912,168 @@ mod test {
         test_false(style, content_false2);
         test_false(style, content_false3);
     }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(
+            CaseStyle::ScreamingSnakeCase.convert("loopStart"),
+            "LOOP_START"
+        );
+        assert_eq!(CaseStyle::SnakeCase.convert("LOOPStart2"), "loop_start2");
+        assert_eq!(CaseStyle::LowerCamelCase.convert("print_it"), "printIt");
+        assert_eq!(CaseStyle::UpperCamelCase.convert("loop2_end"), "Loop2End");
+        assert_eq!(CaseStyle::UpperCamelCase.convert_keyword("BRnz"), "Brnz");
+        assert_eq!(
+            CaseStyle::ScreamingSnakeCase.convert_keyword("halt"),
+            "HALT"
+        );
+    }
+
+    fn fix(style: LintStyle, content: &str) -> String {
+        let errors = Linter::new(style, get_ast(content).unwrap())
+            .check()
+            .unwrap_err();
+        apply_fixes(
+            content,
+            errors.iter().filter_map(|error| error.fix().as_ref()),
+        )
+        .0
+    }
+
+    #[test]
+    fn test_fix() {
+        let content = ".orig x3000\nloop: add R0, R0, #-1\nBRp loop\nloopEnd HALT\n.end\n";
+        assert_eq!(
+            fix(DEFAULT_STYLE, content),
+            ".ORIG x3000\nLOOP ADD R0, R0, #-1\nBRP LOOP\nLOOP_END HALT\n.END\n"
+        );
+        let style = LintStyle {
+            colon_after_label: true,
+            label_style: CaseStyle::LowerCamelCase,
+            instruction_style: CaseStyle::SnakeCase,
+            directive_style: CaseStyle::SnakeCase,
+        };
+        assert_eq!(
+            fix(style, content),
+            ".orig x3000\nloop: add R0, R0, #-1\nbrp loop\nloopEnd: halt\n.end\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_collision() {
+        // renaming `loop` would merge it with `LOOP`, only the instruction is fixed
+        let content = "loop add R0, R0, #1\nLOOP BR loop\n";
+        assert_eq!(
+            fix(DEFAULT_STYLE, content),
+            "loop ADD R0, R0, #1\nLOOP BR loop\n"
+        );
+    }
+
+    struct NoHalt;
+
+    impl ProgramItemVisitor for NoHalt {
+        fn id(&self) -> &'static str {
+            "no-halt"
+        }
+
+        fn default_level(&self) -> RuleLevel {
+            RuleLevel::Error
+        }
+
+        fn visit_instruction(
+            &mut self,
+            _: &[Label],
+            instruction: &Instruction,
+            _: &Option<Comment>,
+            _: &LineColumn,
+        ) -> Vec<Error> {
+            match instruction.instruction_type() {
+                InstructionType::Halt => vec![Error::new(
+                    ErrorKind::Custom("X001", "HALT is not allowed".to_string()),
+                    instruction.span(),
+                )],
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_rule_levels() {
+        let content = "loop: add R0, R0, #1\nHALT";
+        let mut linter = Linter::new(DEFAULT_STYLE, get_ast(content).unwrap());
+        assert_eq!(
+            linter.rule_ids(),
+            vec![
+                "label-case",
+                "label-colon",
+                "instruction-case",
+                "directive-case",
+                "unreachable-code",
+                "program-structure",
+                "return-address",
+                "uninitialized",
+                "data-fall-through",
+                "reserved-opcode",
+                "unused-label",
+                "file-header",
+                "subroutine-doc",
+                "line-length",
+                "unused-suppression"
+            ]
+        );
+        assert!(linter.set_level("label-case", RuleLevel::Error));
+        assert!(linter.set_level("label-colon", RuleLevel::Off));
+        assert!(linter.set_level("program-structure", RuleLevel::Off));
+        assert!(!linter.set_level("unknown", RuleLevel::Off));
+        linter.register(Box::new(NoHalt));
+        let errors = linter.check().unwrap_err();
+        let reported: Vec<_> = errors
+            .iter()
+            .map(|error| (*error.rule(), error.code(), *error.severity()))
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                ("label-case", "L001", Severity::Error),
+                ("instruction-case", "L003", Severity::Warning),
+                ("no-halt", "X001", Severity::Error),
+            ]
+        );
+        assert_eq!(errors[2].kind().to_string(), "HALT is not allowed");
+    }
+
+    #[test]
+    fn test_suppressions() {
+        let content = r#".ORIG x3000
+; lc3lint-disable-next-line label-case
+loop AND R0, R0, #0
+; lc3lint-disable L003 -- generated code
+add R0, R0, #1
+BRz LOOP ; lc3lint-disable-next-line
+; lc3lint-enable
+not R0, R0
+; lc3lint-disable
+.end
+"#;
+        let errors = Linter::new(DEFAULT_STYLE, get_ast(content).unwrap())
+            .check()
+            .unwrap_err();
+        let reported: Vec<_> = errors
+            .iter()
+            .map(|error| (error.code(), *error.rule()))
+            .collect();
+        assert_eq!(
+            reported,
+            vec![("L005", "unused-suppression"), ("L003", "instruction-case")]
+        );
+        // the unused comment is the inline one on line 6
+        assert_eq!(
+            *errors[0].span().start(),
+            content.find("; lc3lint-disable-next-line\n").unwrap()
+        );
+
+        let mut linter = Linter::new(DEFAULT_STYLE, get_ast(content).unwrap());
+        linter.set_level("unused-suppression", RuleLevel::Off);
+        assert_eq!(linter.check().unwrap_err().len(), 1);
+    }
 }
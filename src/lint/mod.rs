@@ -1,3 +1,28 @@
+mod comments;
+mod config;
+mod fall_through;
+mod flow;
 mod linter;
+mod report;
+mod reserved;
+mod return_address;
+mod structure;
+mod style;
+mod suppression;
+mod uninitialized;
+mod unreachable;
+mod unused;
 
+pub use comments::*;
+pub use config::*;
+pub use fall_through::*;
 pub use linter::*;
+pub use report::*;
+pub use reserved::*;
+pub use return_address::*;
+pub use structure::*;
+pub use style::*;
+pub use suppression::UNUSED_SUPPRESSION;
+pub use uninitialized::*;
+pub use unreachable::*;
+pub use unused::*;
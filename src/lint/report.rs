@@ -0,0 +1,281 @@
+use crate::ast::processed_ast::LineColumn;
+use crate::diagnostic::{Diagnostic, Severity};
+use getset::Getters;
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+const TOOL_NAME: &str = "lc3lint";
+
+/// How `lc3lint` reports what it finds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    // rendered against the source, on stderr
+    Human,
+    Json,
+    Sarif,
+    // GitHub Actions workflow commands
+    Github,
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 4] = ["human", "json", "sarif", "github"];
+
+    /// Renders the reports of every checked file, `None` for the human format.
+    pub fn render(self, reports: &[FileReport]) -> Option<String> {
+        match self {
+            OutputFormat::Human => None,
+            OutputFormat::Json => Some(to_json(reports)),
+            OutputFormat::Sarif => Some(to_sarif(reports)),
+            OutputFormat::Github => Some(to_github(reports)),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            "github" => Ok(OutputFormat::Github),
+            _ => Err(format!(
+                "Unknown output format `{s}`, expected one of {}",
+                OutputFormat::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Diagnostics found in one file, with the source their spans point into.
+#[derive(Debug, Getters)]
+pub struct FileReport {
+    #[get = "pub"]
+    path: String,
+    #[get = "pub"]
+    source: String,
+    #[get = "pub"]
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl FileReport {
+    pub fn new(path: String, source: String, diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            path,
+            source,
+            diagnostics,
+        }
+    }
+
+    // start and end of every diagnostic, the end column is exclusive
+    fn located(&self) -> impl Iterator<Item = (&Diagnostic, LineColumn, LineColumn)> {
+        let line_starts = LineColumn::line_starts(&self.source);
+        self.diagnostics.iter().map(move |diagnostic| {
+            let span = diagnostic.span();
+            (
+                diagnostic,
+                LineColumn::locate(&self.source, &line_starts, *span.start()),
+                LineColumn::locate(&self.source, &line_starts, *span.end()),
+            )
+        })
+    }
+}
+
+pub fn to_json(reports: &[FileReport]) -> String {
+    let records: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.located().map(|(diagnostic, start, end)| {
+                json!({
+                    "file": report.path,
+                    "line": start.line(),
+                    "column": start.column(),
+                    "end_line": end.line(),
+                    "end_column": end.column(),
+                    "rule": diagnostic.rule(),
+                    "code": diagnostic.code(),
+                    "severity": diagnostic.severity().to_string(),
+                    "message": diagnostic.summary(),
+                })
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&records).unwrap()
+}
+
+/// SARIF 2.1.0 log with a single run, as accepted by GitHub code scanning.
+/// Results are identified by the id of their lint rule, or by their code for
+/// the checks that are not lint rules.
+pub fn to_sarif(reports: &[FileReport]) -> String {
+    let rules: BTreeSet<&str> = reports
+        .iter()
+        .flat_map(|report| report.diagnostics.iter().map(sarif_rule_id))
+        .collect();
+    let results: Vec<Value> = reports
+        .iter()
+        .flat_map(|report| {
+            report.located().map(|(diagnostic, start, end)| {
+                json!({
+                    "ruleId": sarif_rule_id(diagnostic),
+                    "level": diagnostic.severity().to_string(),
+                    "message": {"text": diagnostic.summary()},
+                    "properties": {"code": diagnostic.code()},
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": {"uri": report.path.replace('\\', "/")},
+                            "region": {
+                                "startLine": start.line(),
+                                "startColumn": start.column(),
+                                "endLine": end.line(),
+                                "endColumn": end.column(),
+                            },
+                        },
+                    }],
+                })
+            })
+        })
+        .collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules.iter().map(|id| json!({"id": id})).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap()
+}
+
+fn sarif_rule_id(diagnostic: &Diagnostic) -> &'static str {
+    diagnostic.rule().unwrap_or(*diagnostic.code())
+}
+
+/// One `::warning` or `::error` workflow command per diagnostic.
+pub fn to_github(reports: &[FileReport]) -> String {
+    let mut output = String::new();
+    for report in reports {
+        for (diagnostic, start, end) in report.located() {
+            let command = match diagnostic.severity() {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            output.push_str(&format!(
+                "::{command} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+                escape_property(&report.path),
+                start.line(),
+                start.column(),
+                end.line(),
+                end.column(),
+                escape_property(&format!("{TOOL_NAME} {}", diagnostic.code())),
+                escape_data(&diagnostic.summary()),
+            ));
+        }
+    }
+    output
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::get_ast;
+    use crate::lint::{DEFAULT_STYLE, Linter};
+
+    fn report(content: &str) -> FileReport {
+        let program = get_ast(content).unwrap();
+        let errors = Linter::new(DEFAULT_STYLE, program).check().unwrap_err();
+        FileReport::new(
+            "src/main.asm".to_string(),
+            content.to_string(),
+            errors.iter().map(Diagnostic::from).collect(),
+        )
+    }
+
+    #[test]
+    fn test_json() {
+        let reports = [report(".ORIG x3000\n  and R0, R0, #0\n.END\n")];
+        let records: Value = serde_json::from_str(&to_json(&reports)).unwrap();
+        assert_eq!(
+            records,
+            json!([{
+                "file": "src/main.asm",
+                "line": 2,
+                "column": 3,
+                "end_line": 2,
+                "end_column": 6,
+                "rule": "instruction-case",
+                "code": "L003",
+                "severity": "warning",
+                "message": "Invalid case style: found SnakeCase, expected ScreamingSnakeCase",
+            }])
+        );
+    }
+
+    #[test]
+    fn test_json_syntax_error() {
+        let content = ".ORIG x3000\nADD R1, R2\n.END\n";
+        let error = get_ast(content).unwrap_err();
+        let reports = [FileReport::new(
+            "src/main.asm".to_string(),
+            content.to_string(),
+            vec![Diagnostic::from(error.as_ref())],
+        )];
+        let records: Value = serde_json::from_str(&to_json(&reports)).unwrap();
+        assert_eq!(records[0]["rule"], Value::Null);
+        assert_eq!(records[0]["code"], "P001");
+    }
+
+    #[test]
+    fn test_sarif() {
+        let reports = [report(".ORIG x3000\nloop and R0, R0, #0\n.END\n")];
+        let log: Value = serde_json::from_str(&to_sarif(&reports)).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{"id": "instruction-case"}, {"id": "label-case"}])
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "label-case");
+        assert_eq!(results[0]["properties"]["code"], "L001");
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"],
+            json!({"startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 5})
+        );
+    }
+
+    #[test]
+    fn test_github() {
+        let reports = [report(".ORIG x3000\nHALT\n.end\n")];
+        assert_eq!(
+            to_github(&reports),
+            "::warning file=src/main.asm,line=3,col=1,endLine=3,endColumn=5,title=lc3lint L004::\
+             Invalid case style: found SnakeCase, expected ScreamingSnakeCase\n"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("50%\nrest"), "50%25%0Arest");
+        assert_eq!(escape_property("a:b,c"), "a%3Ab%2Cc");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::ast::processed_ast::Program;
+use crate::ast::raw_ast::DirectiveType;
+use crate::lint::flow::FlowGraph;
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+
+// opcode no instruction has, running it raises an illegal opcode exception
+const RESERVED_OPCODE: u16 = 0b1101;
+
+/// `.FILL` words with the reserved opcode 1101 that a reachable instruction
+/// falls through or jumps into.
+#[derive(Default)]
+pub struct ReservedOpcode {
+    errors: Vec<Error>,
+}
+
+impl ReservedOpcode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProgramItemVisitor for ReservedOpcode {
+    fn id(&self) -> &'static str {
+        "reserved-opcode"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        let graph = FlowGraph::new(program);
+        self.errors = graph
+            .nodes()
+            .iter()
+            .zip(graph.reachable())
+            .filter(|(_, reachable)| *reachable)
+            .flat_map(|(node, _)| {
+                let falls_into = node
+                    .falls_into()
+                    .map(|directive| (directive, "Execution continues past this instruction"));
+                let jumps_into = node.jumps_into().map(|directive| (directive, "Jumps here"));
+                falls_into
+                    .into_iter()
+                    .chain(jumps_into)
+                    .filter(|(directive, _)| match directive.directive_type() {
+                        DirectiveType::FILL(value) => {
+                            (*value.value() as u16) >> 12 == RESERVED_OPCODE
+                        }
+                        _ => false,
+                    })
+                    .map(|(directive, message)| {
+                        Error::new(
+                            ErrorKind::ReservedOpcode(directive.content().clone()),
+                            directive.span(),
+                        )
+                        .with_related(node.instruction().full_span(), message)
+                    })
+            })
+            .collect();
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::lint_errors;
+
+    fn reserved(content: &str) -> Vec<(&str, &str)> {
+        let text = |span: &crate::ast::raw_ast::Span| &content[*span.start()..*span.end()];
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "reserved-opcode")
+            .map(|error| (text(error.span()), text(&error.related()[0].0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_reserved_opcode() {
+        let content = r#".ORIG x3000
+AND R0, R0, #0
+BRz TRAPPED
+.FILL xD000
+HALT
+TRAPPED .FILL xDFFF
+.END
+"#;
+        assert_eq!(
+            reserved(content),
+            vec![(".FILL", "BRz TRAPPED"), (".FILL", "BRz TRAPPED")]
+        );
+    }
+
+    #[test]
+    fn test_no_reserved_opcode() {
+        let content = r#".ORIG x3000
+LD R0, WORD
+BRz NEXT
+.FILL xC000
+NEXT HALT
+WORD .FILL xD000
+.END
+"#;
+        assert!(reserved(content).is_empty());
+    }
+}
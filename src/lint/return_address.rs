@@ -0,0 +1,225 @@
+use crate::ast::processed_ast::Program;
+use crate::ast::raw_ast::{Instruction, InstructionType, RegisterType};
+use crate::lint::flow::FlowGraph;
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+use either::Either;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Subroutines that call another subroutine or a trap, overwriting R7, and
+/// then return through it without having saved and restored it.
+#[derive(Default)]
+pub struct ReturnAddress {
+    errors: Vec<Error>,
+}
+
+// what R7 holds at some point of a subroutine
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct State {
+    // node that overwrote the return address, if it is gone
+    clobbered: Option<usize>,
+    // whether the return address was stored somewhere it can be restored from
+    saved: bool,
+}
+
+impl ReturnAddress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // every return reached with R7 overwritten, first by the shortest path
+    fn check(&mut self, program: &Program, graph: &FlowGraph, name: &str, entry: usize) {
+        let nodes = graph.nodes();
+        let start = State {
+            clobbered: None,
+            saved: false,
+        };
+        let mut previous: HashMap<(usize, State), Option<(usize, State)>> =
+            HashMap::from([((entry, start), None)]);
+        let mut queue = VecDeque::from([(entry, start)]);
+        let mut reported = HashSet::new();
+        while let Some((index, state)) = queue.pop_front() {
+            let instruction = nodes[index].instruction();
+            if is_return(instruction) {
+                let Some(call) = state.clobbered else {
+                    continue;
+                };
+                if !reported.insert(call) {
+                    continue;
+                }
+                let mut path = vec![];
+                let mut step = Some((index, state));
+                while let Some((node, state)) = step {
+                    path.push(program.line_of(*nodes[node].instruction().span().start()));
+                    step = previous[&(node, state)];
+                }
+                path.reverse();
+                let clobber = nodes[call].instruction();
+                self.errors.push(
+                    Error::new(
+                        ErrorKind::ClobberedReturn(
+                            name.to_owned(),
+                            clobber.content().clone(),
+                            path,
+                        ),
+                        clobber.full_span(),
+                    )
+                    .with_related(instruction.span(), "Returns through the overwritten R7"),
+                );
+                continue;
+            }
+            let next = transfer(instruction, index, state);
+            for &successor in nodes[index].successors() {
+                if let Entry::Vacant(entry) = previous.entry((successor, next)) {
+                    entry.insert(Some((index, state)));
+                    queue.push_back((successor, next));
+                }
+            }
+        }
+    }
+}
+
+impl ProgramItemVisitor for ReturnAddress {
+    fn id(&self) -> &'static str {
+        "return-address"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.errors.clear();
+        let graph = FlowGraph::new(program);
+        for (name, entry) in graph.subroutines() {
+            self.check(program, &graph, name, entry);
+        }
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+fn is_r7(register_type: &RegisterType) -> bool {
+    *register_type == RegisterType::R7
+}
+
+fn is_return(instruction: &Instruction) -> bool {
+    match instruction.instruction_type() {
+        InstructionType::Ret => true,
+        InstructionType::Jmp(base) => is_r7(base.register_type()),
+        _ => false,
+    }
+}
+
+// state of R7 after `instruction`, the node at `index`, runs
+fn transfer(instruction: &Instruction, index: usize, state: State) -> State {
+    let clobber = State {
+        clobbered: state.clobbered.or(Some(index)),
+        ..state
+    };
+    if instruction.is_call() {
+        return clobber;
+    }
+    match instruction.instruction_type() {
+        InstructionType::St(sr, _) | InstructionType::Sti(sr, _) | InstructionType::Str(sr, ..)
+            if is_r7(sr.register_type()) && state.clobbered.is_none() =>
+        {
+            return State {
+                saved: true,
+                ..state
+            };
+        }
+        // `ADD Rn, R7, #0` keeps a copy
+        InstructionType::Add(dr, sr, Either::Right(immediate))
+            if is_r7(sr.register_type())
+                && !is_r7(dr.register_type())
+                && *immediate.value() == 0
+                && state.clobbered.is_none() =>
+        {
+            return State {
+                saved: true,
+                ..state
+            };
+        }
+        _ => {}
+    }
+    match instruction.destination() {
+        Some(dr) if is_r7(dr.register_type()) => {
+            let restores = match instruction.instruction_type() {
+                InstructionType::Ld(..) | InstructionType::Ldi(..) | InstructionType::Ldr(..) => {
+                    true
+                }
+                InstructionType::Add(_, _, Either::Right(immediate)) => *immediate.value() == 0,
+                _ => false,
+            };
+            match state.saved && restores {
+                true => State {
+                    clobbered: None,
+                    ..state
+                },
+                false => clobber,
+            }
+        }
+        _ => state,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{ErrorKind, lint_errors};
+
+    fn clobbered(content: &str) -> Vec<(String, String, Vec<usize>)> {
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "return-address")
+            .filter_map(|error| match error.kind() {
+                ErrorKind::ClobberedReturn(subroutine, call, path) => {
+                    Some((subroutine.clone(), call.clone(), path.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clobbered() {
+        let content = r#".ORIG x3000
+JSR PRINT
+HALT
+PRINT ADD R0, R0, #0
+BRz DONE
+OUT
+DONE RET
+.END
+"#;
+        assert_eq!(
+            clobbered(content),
+            vec![("PRINT".to_string(), "OUT".to_string(), vec![4, 5, 6, 7])]
+        );
+    }
+
+    #[test]
+    fn test_saved() {
+        let content = r#".ORIG x3000
+JSR OUTER
+JSR COPY
+HALT
+OUTER ST R7, SAVE
+JSR INNER
+LD R7, SAVE
+RET
+INNER RET
+COPY ADD R6, R7, #0
+TRAP x21
+ADD R7, R6, #0
+JMP R7
+SAVE .BLKW #1
+.END
+"#;
+        assert!(clobbered(content).is_empty());
+        let content =
+            ".ORIG x3000\nJSR SUB\nHALT\nSUB ST R7, SAVE\nGETC\nRET\nSAVE .BLKW #1\n.END\n";
+        assert_eq!(
+            clobbered(content),
+            vec![("SUB".to_string(), "GETC".to_string(), vec![4, 5, 6])]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use crate::asm::directive_size;
+use crate::ast::processed_ast::{LineColumn, Program};
+use crate::ast::raw_ast::{Comment, Directive, DirectiveType, Instruction, Label, Span};
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+
+/// Every `.ORIG` closed by an `.END`, nothing outside of those blocks, and no
+/// two blocks loaded into the same memory.
+#[derive(Default)]
+pub struct ProgramStructure {
+    // `.ORIG` of the open block, its origin and the number of words in it so far
+    block: Option<(Span, u16, u32)>,
+    // first and last address of every non-empty block closed so far
+    blocks: Vec<(u32, u32)>,
+    // whether an `.END` came before, telling content after it from content before `.ORIG`
+    after_end: bool,
+    // start and end of the items outside of any block seen so far
+    outside: Option<(usize, usize)>,
+}
+
+impl ProgramStructure {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // puts an item with a size in the open block, or in the region outside of any block
+    fn occupy(&mut self, span: &Span, size: u32) {
+        match &mut self.block {
+            Some((_, _, words)) => *words += size,
+            None => {
+                let start = self.outside.map_or(*span.start(), |(start, _)| start);
+                self.outside = Some((start, *span.end()));
+            }
+        }
+    }
+
+    // ends the region outside of any block, reporting it if it has any items
+    fn flush(&mut self) -> Vec<Error> {
+        match self.outside.take() {
+            Some((start, end)) => vec![Error::new(
+                ErrorKind::OutsideBlock(self.after_end),
+                &Span::new(start, end),
+            )],
+            None => vec![],
+        }
+    }
+
+    // ends the open block, reporting it if it overlaps one closed before
+    fn close(&mut self) -> Vec<Error> {
+        let Some((span, origin, words)) = self.block.take() else {
+            return vec![];
+        };
+        if words == 0 {
+            return vec![];
+        }
+        let (first, last) = (origin as u32, origin as u32 + words - 1);
+        let overlapped = self
+            .blocks
+            .iter()
+            .find(|(start, end)| first <= *end && *start <= last)
+            .map(|(start, _)| *start as u16);
+        self.blocks.push((first, last));
+        match overlapped {
+            Some(other) => vec![Error::new(ErrorKind::OverlappingBlock(other), &span)],
+            None => vec![],
+        }
+    }
+}
+
+impl ProgramItemVisitor for ProgramStructure {
+    fn id(&self) -> &'static str {
+        "program-structure"
+    }
+
+    fn prepare(&mut self, _: &Program) {
+        *self = Self::default();
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _: &[Label],
+        instruction: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.occupy(instruction.full_span(), 1);
+        vec![]
+    }
+
+    fn visit_directive(
+        &mut self,
+        _: &[Label],
+        directive: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        match directive.directive_type() {
+            DirectiveType::ORIG(address) => {
+                let mut errors = self.flush();
+                if let Some((span, ..)) = &self.block {
+                    errors.push(Error::new(ErrorKind::MissingEnd, span));
+                }
+                errors.extend(self.close());
+                self.block = Some((directive.span().clone(), *address.value(), 0));
+                errors
+            }
+            DirectiveType::END => {
+                let mut errors = self.flush();
+                match self.block.is_some() {
+                    true => errors.extend(self.close()),
+                    false => errors.push(Error::new(ErrorKind::UnmatchedEnd, directive.span())),
+                }
+                self.after_end = true;
+                errors
+            }
+            _ => {
+                self.occupy(directive.span(), directive_size(directive));
+                vec![]
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        let mut errors = self.flush();
+        if let Some((span, ..)) = &self.block {
+            errors.push(Error::new(ErrorKind::MissingEnd, span));
+        }
+        errors.extend(self.close());
+        errors
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{ErrorKind, lint_errors};
+
+    fn structure(content: &str) -> Vec<(String, &str)> {
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "program-structure")
+            .map(|error| {
+                (
+                    error.kind().to_string(),
+                    &content[*error.span().start()..*error.span().end()],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pairing() {
+        let content = r#"ADD R0, R0, #1
+NOT R0, R0
+.ORIG x3000
+HALT
+.ORIG x4000
+HALT
+.END
+.END
+DATA .FILL #1
+.ORIG x5000
+HALT
+"#;
+        assert_eq!(
+            structure(content),
+            vec![
+                (
+                    ErrorKind::OutsideBlock(false).to_string(),
+                    "ADD R0, R0, #1\nNOT R0, R0"
+                ),
+                (ErrorKind::MissingEnd.to_string(), ".ORIG"),
+                (ErrorKind::UnmatchedEnd.to_string(), ".END"),
+                (ErrorKind::OutsideBlock(true).to_string(), ".FILL"),
+                (ErrorKind::MissingEnd.to_string(), ".ORIG"),
+            ]
+        );
+        assert!(structure(".ORIG x3000\nHALT\n.END\n.ORIG x4000\n.END\n; done\n").is_empty());
+    }
+
+    #[test]
+    fn test_overlap() {
+        let content = r#".ORIG x3000
+.BLKW #4
+.END
+.ORIG x3004
+HALT
+.END
+.ORIG x3003
+.STRINGZ "ab"
+.END
+.ORIG x3000
+.END
+"#;
+        let errors = structure(content);
+        assert_eq!(
+            errors,
+            vec![(ErrorKind::OverlappingBlock(0x3000).to_string(), ".ORIG")]
+        );
+        assert_eq!(errors[0].0, "Block overlaps the block starting at x3000");
+    }
+}
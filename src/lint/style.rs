@@ -0,0 +1,323 @@
+use crate::ast::processed_ast::{LineColumn, Program, ProgramItem};
+use crate::ast::raw_ast::{Comment, Directive, Instruction, Label, Span};
+use crate::diagnostic::Fix;
+use crate::lint::{CaseStyle, Error, ErrorKind, ProgramItemVisitor, StyleTarget};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+static LOWER_CAMEL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]+(?:[A-Z][a-z0-9]*)*$").unwrap());
+static UPPER_CAMEL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Z][a-z0-9]*(?:[A-Z][a-z0-9]*)*$").unwrap());
+static SNAKE_CASE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]+(?:_[a-z0-9]+)*$").unwrap());
+static SCREAMING_SNAKE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Z0-9]+(?:_[A-Z0-9]+)*$").unwrap());
+
+/// Label names in the configured case, fixed by renaming every reference too.
+pub struct LabelCase {
+    style: CaseStyle,
+    // label names, so a rename never merges two labels
+    definitions: HashSet<String>,
+    // reference spans by label name, renamed along with the definition
+    references: HashMap<String, Vec<Span>>,
+    // names already given out, so every definition of a name gets the same one
+    renames: HashMap<String, String>,
+}
+
+/// A colon after every label, or after none.
+pub struct LabelColon {
+    colon_after_label: bool,
+}
+
+/// Instruction mnemonics in the configured case.
+pub struct InstructionCase {
+    style: CaseStyle,
+}
+
+/// Directive names in the configured case.
+pub struct DirectiveCase {
+    style: CaseStyle,
+}
+
+impl LabelCase {
+    pub fn new(style: CaseStyle) -> Self {
+        Self {
+            style,
+            definitions: HashSet::new(),
+            references: HashMap::new(),
+            renames: HashMap::new(),
+        }
+    }
+
+    fn check(&mut self, labels: &[Label]) -> Vec<Error> {
+        let mut errors = vec![];
+        for label in labels {
+            if let Err(found) = check_keyword_style(label.name(), &self.style) {
+                let fix = self.fix(label);
+                errors.push(
+                    Error::new(
+                        ErrorKind::CaseStyle(StyleTarget::Label, self.style, found),
+                        label.span(),
+                    )
+                    .with_fix(fix),
+                );
+            }
+        }
+        errors
+    }
+
+    fn fix(&mut self, label: &Label) -> Option<Fix> {
+        let name = label.name();
+        let renamed = match self.renames.get(name) {
+            Some(renamed) => renamed.clone(),
+            None => {
+                let candidate = self.style.convert(name);
+                if check_keyword_style(&candidate, &self.style).is_err()
+                    || self.definitions.contains(&candidate)
+                {
+                    return None;
+                }
+                self.definitions.insert(candidate.clone());
+                self.renames.insert(name.to_owned(), candidate.clone());
+                candidate
+            }
+        };
+        // the colon is left to `LabelColon`
+        let start = *label.span().start();
+        let mut edits = vec![(Span::new(start, start + name.len()), renamed.clone())];
+        let references = self.references.get(name).into_iter().flatten();
+        edits.extend(references.map(|span| (span.clone(), renamed.clone())));
+        Some(Fix::new(format!("Rename `{name}` to `{renamed}`"), edits))
+    }
+}
+
+impl ProgramItemVisitor for LabelCase {
+    fn id(&self) -> &'static str {
+        "label-case"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.definitions.clear();
+        self.references.clear();
+        self.renames.clear();
+        for item in program.items() {
+            match item {
+                ProgramItem::Instruction(labels, instruction, ..) => {
+                    self.definitions
+                        .extend(labels.iter().map(|label| label.name().to_owned()));
+                    if let Some(reference) = instruction.label_reference() {
+                        self.references
+                            .entry(reference.content().clone())
+                            .or_default()
+                            .push(reference.span().clone());
+                    }
+                }
+                ProgramItem::Directive(labels, ..) | ProgramItem::EOL(labels) => {
+                    self.definitions
+                        .extend(labels.iter().map(|label| label.name().to_owned()));
+                }
+                ProgramItem::Comment(..) | ProgramItem::Error(..) => {}
+            }
+        }
+    }
+
+    fn visit_instruction(
+        &mut self,
+        labels: &[Label],
+        _: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.check(labels)
+    }
+
+    fn visit_directive(
+        &mut self,
+        labels: &[Label],
+        _: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.check(labels)
+    }
+
+    fn visit_eol(&mut self, labels: &[Label]) -> Vec<Error> {
+        self.check(labels)
+    }
+}
+
+impl LabelColon {
+    pub fn new(colon_after_label: bool) -> Self {
+        Self { colon_after_label }
+    }
+
+    fn check(&self, labels: &[Label]) -> Vec<Error> {
+        labels
+            .iter()
+            .filter(|label| label.content().ends_with(":") != self.colon_after_label)
+            .map(|label| {
+                let end = *label.span().end();
+                let fix = match self.colon_after_label {
+                    true => Fix::new("Add a colon", vec![(Span::new(end, end), ":".to_string())]),
+                    false => Fix::new(
+                        "Remove the colon",
+                        vec![(Span::new(end - 1, end), String::new())],
+                    ),
+                };
+                Error::new(ErrorKind::ColonStyle(self.colon_after_label), label.span())
+                    .with_fix(Some(fix))
+            })
+            .collect()
+    }
+}
+
+impl ProgramItemVisitor for LabelColon {
+    fn id(&self) -> &'static str {
+        "label-colon"
+    }
+
+    fn visit_instruction(
+        &mut self,
+        labels: &[Label],
+        _: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.check(labels)
+    }
+
+    fn visit_directive(
+        &mut self,
+        labels: &[Label],
+        _: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.check(labels)
+    }
+
+    fn visit_eol(&mut self, labels: &[Label]) -> Vec<Error> {
+        self.check(labels)
+    }
+}
+
+impl InstructionCase {
+    pub fn new(style: CaseStyle) -> Self {
+        Self { style }
+    }
+}
+
+impl ProgramItemVisitor for InstructionCase {
+    fn id(&self) -> &'static str {
+        "instruction-case"
+    }
+
+    fn visit_instruction(
+        &mut self,
+        _: &[Label],
+        instruction: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        match check_keyword_style(instruction.content(), &self.style) {
+            Ok(_) => vec![],
+            Err(found) => vec![
+                Error::new(
+                    ErrorKind::CaseStyle(StyleTarget::Instruction, self.style, found),
+                    instruction.span(),
+                )
+                .with_fix(keyword_fix(
+                    instruction.content(),
+                    instruction.span(),
+                    self.style,
+                )),
+            ],
+        }
+    }
+}
+
+impl DirectiveCase {
+    pub fn new(style: CaseStyle) -> Self {
+        Self { style }
+    }
+}
+
+impl ProgramItemVisitor for DirectiveCase {
+    fn id(&self) -> &'static str {
+        "directive-case"
+    }
+
+    fn visit_directive(
+        &mut self,
+        _: &[Label],
+        directive: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        assert!(directive.content().starts_with("."));
+        match check_keyword_style(directive.content().strip_prefix(".").unwrap(), &self.style) {
+            Ok(_) => vec![],
+            Err(found) => vec![
+                Error::new(
+                    ErrorKind::CaseStyle(StyleTarget::Directive, self.style, found),
+                    directive.span(),
+                )
+                .with_fix(keyword_fix(
+                    directive.content(),
+                    directive.span(),
+                    self.style,
+                )),
+            ],
+        }
+    }
+}
+
+#[allow(clippy::if_same_then_else)]
+fn check_keyword_style(keyword: &str, case_style: &CaseStyle) -> Result<(), Option<CaseStyle>> {
+    let found_style = match get_identifier_style(keyword) {
+        None => {
+            return Err(None);
+        }
+        Some(st) => st,
+    };
+    if &found_style == case_style {
+        Ok(())
+    }
+    // snakecase is a subset of a lower camelcase without _
+    else if found_style == CaseStyle::SnakeCase
+        && (!keyword.contains("_"))
+        && *case_style == CaseStyle::LowerCamelCase
+    {
+        Ok(())
+    } else {
+        Err(Some(found_style))
+    }
+}
+
+fn get_identifier_style(identifier: &str) -> Option<CaseStyle> {
+    if SNAKE_CASE.is_match(identifier) {
+        Some(CaseStyle::SnakeCase)
+    } else if SCREAMING_SNAKE.is_match(identifier) {
+        Some(CaseStyle::ScreamingSnakeCase)
+    } else if LOWER_CAMEL.is_match(identifier) {
+        Some(CaseStyle::LowerCamelCase)
+    } else if UPPER_CAMEL.is_match(identifier) {
+        Some(CaseStyle::UpperCamelCase)
+    } else {
+        None
+    }
+}
+
+fn keyword_fix(keyword: &str, span: &Span, style: CaseStyle) -> Option<Fix> {
+    let (prefix, word) = match keyword.strip_prefix(".") {
+        Some(word) => (".", word),
+        None => ("", keyword),
+    };
+    let converted = style.convert_keyword(word);
+    check_keyword_style(&converted, &style).ok()?;
+    let replacement = format!("{prefix}{converted}");
+    Some(Fix::new(
+        format!("Replace `{keyword}` with `{replacement}`"),
+        vec![(span.clone(), replacement)],
+    ))
+}
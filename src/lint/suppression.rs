@@ -0,0 +1,176 @@
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{Comment, Span};
+use crate::lint::{Error, ErrorKind};
+
+/// Id of the check reporting suppression comments that suppress nothing.
+pub const UNUSED_SUPPRESSION: &str = "unused-suppression";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Command {
+    DisableNextLine,
+    Disable,
+    Enable,
+}
+
+// lines on which a suppression comment turns a rule off
+#[derive(Debug)]
+struct Scope {
+    // `None` for every rule
+    rule: Option<String>,
+    // rules enabled again inside a scope covering every rule
+    except: Vec<String>,
+    first_line: usize,
+    // exclusive, `None` until the end of the file
+    end_line: Option<usize>,
+    // index of the comment that opened the scope
+    comment: usize,
+}
+
+/// Suppression comments of a program. `; lc3lint-disable-next-line` covers the
+/// following line, `; lc3lint-disable` everything up to the next
+/// `; lc3lint-enable`. Each names the rules it applies to by id or code,
+/// or applies to every rule when it names none.
+#[derive(Debug)]
+pub(crate) struct Suppressions {
+    // span of every disabling comment, and whether it suppressed anything
+    comments: Vec<(Span, bool)>,
+    scopes: Vec<Scope>,
+}
+
+impl Suppressions {
+    pub(crate) fn new(program: &Program) -> Self {
+        let mut suppressions = Self {
+            comments: vec![],
+            scopes: vec![],
+        };
+        for item in program.items() {
+            let comment = match item {
+                ProgramItem::Comment(comment, _) => Some(comment),
+                ProgramItem::Instruction(.., comment, _)
+                | ProgramItem::Directive(.., comment, _) => comment.as_ref(),
+                ProgramItem::EOL(_) | ProgramItem::Error(..) => None,
+            };
+            if let Some(comment) = comment {
+                suppressions.add(comment, program.line_of(*comment.span().start()));
+            }
+        }
+        suppressions
+    }
+
+    fn add(&mut self, comment: &Comment, line: usize) {
+        let Some((command, rules)) = parse(comment.content()) else {
+            return;
+        };
+        if command == Command::Enable {
+            self.enable(&rules, line);
+            return;
+        }
+        let index = self.comments.len();
+        self.comments.push((comment.span().clone(), false));
+        let (first_line, end_line) = match command {
+            Command::DisableNextLine => (line + 1, Some(line + 2)),
+            _ => (line, None),
+        };
+        let rules = match rules.is_empty() {
+            true => vec![None],
+            false => rules.into_iter().map(Some).collect(),
+        };
+        for rule in rules {
+            self.scopes.push(Scope {
+                rule,
+                except: vec![],
+                first_line,
+                end_line,
+                comment: index,
+            });
+        }
+    }
+
+    fn enable(&mut self, rules: &[String], line: usize) {
+        let mut reopened = vec![];
+        for scope in self.scopes.iter_mut() {
+            if scope.end_line.is_some() {
+                continue;
+            }
+            match &scope.rule {
+                Some(rule) if !rules.is_empty() && !rules.contains(rule) => continue,
+                // enabling some rules splits a scope covering every rule
+                None if !rules.is_empty() => reopened.push(Scope {
+                    rule: None,
+                    except: [scope.except.as_slice(), rules].concat(),
+                    first_line: line,
+                    end_line: None,
+                    comment: scope.comment,
+                }),
+                _ => {}
+            }
+            scope.end_line = Some(line);
+        }
+        self.scopes.extend(reopened);
+    }
+
+    /// Whether a comment suppresses the error reported on the line, marking it used.
+    pub(crate) fn suppresses(&mut self, error: &Error, line: usize) -> bool {
+        let names = |name: &String| name == error.rule() || name == error.code();
+        let mut suppressed = false;
+        for scope in &self.scopes {
+            let covers = line >= scope.first_line && scope.end_line.is_none_or(|end| line < end);
+            let applies = match &scope.rule {
+                Some(rule) => names(rule),
+                None => !scope.except.iter().any(names),
+            };
+            if covers && applies {
+                self.comments[scope.comment].1 = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Disabling comments that suppressed nothing.
+    pub(crate) fn unused(&self) -> Vec<Error> {
+        self.comments
+            .iter()
+            .filter(|(_, used)| !used)
+            .map(|(span, _)| Error::new(ErrorKind::UnusedSuppression, span))
+            .collect()
+    }
+}
+
+// the command and the rules it names, text after `--` is a free-form reason
+fn parse(content: &str) -> Option<(Command, Vec<String>)> {
+    let text = content.trim_start_matches(';').trim();
+    let (command, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let command = match command {
+        "lc3lint-disable-next-line" => Command::DisableNextLine,
+        "lc3lint-disable" => Command::Disable,
+        "lc3lint-enable" => Command::Enable,
+        _ => return None,
+    };
+    let rules = rest.split("--").next().unwrap_or_default();
+    let rules = rules
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|rule| !rule.is_empty())
+        .map(str::to_owned)
+        .collect();
+    Some((command, rules))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("; lc3lint-disable-next-line label-case, L003 -- spec name"),
+            Some((
+                Command::DisableNextLine,
+                vec!["label-case".to_string(), "L003".to_string()]
+            ))
+        );
+        assert_eq!(parse(";;lc3lint-enable"), Some((Command::Enable, vec![])));
+        assert_eq!(parse("; lc3lint-disabled"), None);
+        assert_eq!(parse("; disable lc3lint"), None);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::ast::processed_ast::Program;
+use crate::ast::raw_ast::{BrType, Instruction, InstructionType, Register, RegisterType, Span};
+use crate::lint::flow::FlowGraph;
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+use either::Either;
+
+/// Registers read and condition codes tested before anything set them, on
+/// some path from the start of a block. Subroutines are left out, their
+/// registers are set by the caller.
+#[derive(Default)]
+pub struct Uninitialized {
+    errors: Vec<Error>,
+}
+
+// what is surely set before an instruction runs, whichever path led there
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct State {
+    // bit n for Rn
+    registers: u8,
+    condition_codes: bool,
+}
+
+impl State {
+    const UNSET: State = State {
+        registers: 0,
+        condition_codes: false,
+    };
+    const SET: State = State {
+        registers: u8::MAX,
+        condition_codes: true,
+    };
+
+    fn meet(self, other: State) -> State {
+        State {
+            registers: self.registers & other.registers,
+            condition_codes: self.condition_codes && other.condition_codes,
+        }
+    }
+
+    fn has(&self, register_type: RegisterType) -> bool {
+        self.registers & bit(register_type) != 0
+    }
+}
+
+impl Uninitialized {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProgramItemVisitor for Uninitialized {
+    fn id(&self) -> &'static str {
+        "uninitialized"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.errors.clear();
+        let graph = FlowGraph::new(program);
+        let nodes = graph.nodes();
+        let mut states: Vec<Option<State>> = vec![None; nodes.len()];
+        let mut pending = graph.entries().to_vec();
+        for &entry in graph.entries() {
+            states[entry] = Some(State::UNSET);
+        }
+        while let Some(index) = pending.pop() {
+            let after = transfer(nodes[index].instruction(), states[index].unwrap());
+            for &successor in nodes[index].successors() {
+                let state = match states[successor] {
+                    Some(state) => state.meet(after),
+                    None => after,
+                };
+                if states[successor] != Some(state) {
+                    states[successor] = Some(state);
+                    pending.push(successor);
+                }
+            }
+        }
+        for (node, state) in nodes.iter().zip(states) {
+            let Some(state) = state else {
+                continue;
+            };
+            let instruction = node.instruction();
+            for (register_type, span) in reads(instruction) {
+                if !state.has(register_type) {
+                    self.errors.push(Error::new(
+                        ErrorKind::UninitializedRegister(format!("{register_type:?}")),
+                        &span,
+                    ));
+                }
+            }
+            if tests_condition_codes(instruction) && !state.condition_codes {
+                self.errors.push(Error::new(
+                    ErrorKind::UnsetConditionCodes(instruction.content().clone()),
+                    instruction.span(),
+                ));
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+fn bit(register_type: RegisterType) -> u8 {
+    1 << register_type as u8
+}
+
+fn operand(register: &Register) -> (RegisterType, Span) {
+    (*register.register_type(), register.span().clone())
+}
+
+// registers the instruction reads, with the span of the operand naming them
+fn reads(instruction: &Instruction) -> Vec<(RegisterType, Span)> {
+    let implicit = |register_type| vec![(register_type, instruction.span().clone())];
+    match instruction.instruction_type() {
+        // `AND Rn, Rn, #0` clears a register whatever it held
+        InstructionType::And(_, _, Either::Right(immediate)) if *immediate.value() == 0 => vec![],
+        InstructionType::Add(_, sr1, Either::Left(sr2))
+        | InstructionType::And(_, sr1, Either::Left(sr2)) => vec![operand(sr1), operand(sr2)],
+        InstructionType::Add(_, sr, Either::Right(_))
+        | InstructionType::And(_, sr, Either::Right(_))
+        | InstructionType::Not(_, sr)
+        | InstructionType::Ldr(_, sr, _)
+        | InstructionType::St(sr, _)
+        | InstructionType::Sti(sr, _)
+        | InstructionType::Jmp(sr)
+        | InstructionType::Jsrr(sr) => vec![operand(sr)],
+        InstructionType::Str(sr, base, _) => vec![operand(sr), operand(base)],
+        InstructionType::Ret => implicit(RegisterType::R7),
+        InstructionType::Puts | InstructionType::Out => implicit(RegisterType::R0),
+        // OUT, PUTS and PUTSP
+        InstructionType::Trap(vector) if matches!(*vector.value(), 0x21 | 0x22 | 0x24) => {
+            implicit(RegisterType::R0)
+        }
+        _ => vec![],
+    }
+}
+
+fn tests_condition_codes(instruction: &Instruction) -> bool {
+    matches!(
+        instruction.instruction_type(),
+        InstructionType::Br(brtype, _) if !matches!(brtype, BrType::Nzp | BrType::None)
+    )
+}
+
+// what is set after the instruction runs
+fn transfer(instruction: &Instruction, state: State) -> State {
+    let mut after = state;
+    match instruction.instruction_type() {
+        // whatever the subroutine sets is taken as set
+        InstructionType::Jsr(_) | InstructionType::Jsrr(_) => return State::SET,
+        // GETC and IN read a character into R0
+        InstructionType::Getc | InstructionType::In => after.registers |= bit(RegisterType::R0),
+        InstructionType::Trap(vector) if matches!(*vector.value(), 0x20 | 0x23) => {
+            after.registers |= bit(RegisterType::R0)
+        }
+        _ => {}
+    }
+    if instruction.is_call() {
+        after.registers |= bit(RegisterType::R7);
+    }
+    if let Some(dr) = instruction.destination() {
+        after.registers |= bit(*dr.register_type());
+        after.condition_codes = true;
+    }
+    after
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{ErrorKind, lint_errors};
+
+    fn uninitialized(content: &str) -> Vec<(String, &str)> {
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "uninitialized")
+            .map(|error| {
+                (
+                    error.kind().to_string(),
+                    &content[*error.span().start()..*error.span().end()],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_uninitialized() {
+        let content = r#".ORIG x3000
+BRZ SKIP
+AND R1, R1, #0
+SKIP ADD R2, R1, R3
+BRP SKIP
+PUTS
+GETC
+OUT
+HALT
+.END
+"#;
+        assert_eq!(
+            uninitialized(content),
+            vec![
+                (
+                    ErrorKind::UnsetConditionCodes("BRZ".to_string()).to_string(),
+                    "BRZ"
+                ),
+                (
+                    ErrorKind::UninitializedRegister("R1".to_string()).to_string(),
+                    "R1"
+                ),
+                (
+                    ErrorKind::UninitializedRegister("R3".to_string()).to_string(),
+                    "R3"
+                ),
+                (
+                    ErrorKind::UninitializedRegister("R0".to_string()).to_string(),
+                    "PUTS"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_initialized() {
+        let content = r#".ORIG x3000
+LEA R0, TEXT
+PUTS
+LD R1, COUNT
+LOOP ADD R1, R1, #-1
+BRP LOOP
+JSR SUB
+ADD R2, R3, R4
+HALT
+SUB ADD R3, R3, R5
+RET
+TEXT .STRINGZ "hi"
+COUNT .FILL #3
+.END
+"#;
+        assert!(uninitialized(content).is_empty());
+    }
+}
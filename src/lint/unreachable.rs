@@ -0,0 +1,171 @@
+use crate::ast::processed_ast::{LineColumn, Program};
+use crate::ast::raw_ast::{Comment, Directive, DirectiveType, Instruction, Label, Span};
+use crate::lint::flow::FlowGraph;
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor};
+use std::collections::HashSet;
+
+/// Instructions following an unconditional transfer of control that no label
+/// or branch leads to.
+#[derive(Default)]
+pub struct UnreachableCode {
+    // start of every instruction a branch or `JSR` targets, by label or by offset
+    targets: HashSet<usize>,
+    // mnemonic of the transfer execution cannot get past, until a label
+    transfer: Option<String>,
+    // start and end of the unreachable instructions seen so far
+    region: Option<(usize, usize)>,
+}
+
+impl UnreachableCode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ends the current region, reporting it if it has any instructions
+    fn flush(&mut self) -> Vec<Error> {
+        match (self.region.take(), &self.transfer) {
+            (Some((start, end)), Some(transfer)) => vec![Error::new(
+                ErrorKind::UnreachableCode(transfer.clone()),
+                &Span::new(start, end),
+            )],
+            _ => vec![],
+        }
+    }
+
+    // a label or a new block makes whatever follows reachable again
+    fn reachable(&mut self) -> Vec<Error> {
+        let errors = self.flush();
+        self.transfer = None;
+        errors
+    }
+}
+
+impl ProgramItemVisitor for UnreachableCode {
+    fn id(&self) -> &'static str {
+        "unreachable-code"
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        let graph = FlowGraph::new(program);
+        let nodes = graph.nodes();
+        *self = Self {
+            targets: nodes
+                .iter()
+                .filter_map(|node| graph.jump_target(node))
+                .map(|target| *nodes[target].instruction().span().start())
+                .collect(),
+            ..Self::default()
+        };
+    }
+
+    fn visit_instruction(
+        &mut self,
+        labels: &[Label],
+        instruction: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        let errors = match labels.is_empty() && !self.targets.contains(instruction.span().start()) {
+            true => vec![],
+            false => self.reachable(),
+        };
+        if self.transfer.is_some() {
+            let span = instruction.full_span();
+            let start = self.region.map_or(*span.start(), |(start, _)| start);
+            self.region = Some((start, *span.end()));
+        } else if instruction.is_unconditional_transfer() {
+            self.transfer = Some(instruction.content().clone());
+        }
+        errors
+    }
+
+    fn visit_directive(
+        &mut self,
+        labels: &[Label],
+        directive: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        match directive.directive_type() {
+            DirectiveType::ORIG(_) | DirectiveType::END => self.reachable(),
+            _ if !labels.is_empty() => self.reachable(),
+            // data splits the region, the instructions after it are still unreachable
+            _ => self.flush(),
+        }
+    }
+
+    fn visit_eol(&mut self, _: &[Label]) -> Vec<Error> {
+        self.reachable()
+    }
+
+    fn finish(&mut self) -> Vec<Error> {
+        self.reachable()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{ErrorKind, lint_errors};
+
+    fn unreachable(content: &str) -> Vec<(String, &str)> {
+        lint_errors(content)
+            .iter()
+            .filter(|error| *error.rule() == "unreachable-code")
+            .filter_map(|error| match error.kind() {
+                ErrorKind::UnreachableCode(transfer) => Some((
+                    transfer.clone(),
+                    &content[*error.span().start()..*error.span().end()],
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let content = r#".ORIG x3000
+LOOP ADD R0, R0, #-1
+BRp LOOP
+BRnzp DONE
+ADD R1, R1, #1
+NOT R1, R1 ; dead
+DONE HALT
+LD R0, #1
+.FILL #1
+ST R0, #-2
+.END
+"#;
+        assert_eq!(
+            unreachable(content),
+            vec![
+                ("BRnzp".to_string(), "ADD R1, R1, #1\nNOT R1, R1"),
+                ("HALT".to_string(), "LD R0, #1"),
+                ("HALT".to_string(), "ST R0, #-2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reachable() {
+        let content = r#".ORIG x3000
+JSR SUB
+TRAP x21
+BR #1
+SUB RET
+NEXT BRz SUB
+JMP R2
+.END
+.ORIG x4000
+NOT R0, R0
+RTI
+"#;
+        assert!(unreachable(content).is_empty());
+        let content =
+            ".ORIG x3000\nAND R0, R0, #0\nBRnzp #1\nHALT\nADD R0, R0, #1\nBRnzp #-3\n.END\n";
+        assert!(unreachable(content).is_empty());
+        assert_eq!(
+            unreachable(".ORIG x3000\nTRAP x25\nADD R0, R0, R0\n"),
+            vec![("TRAP".to_string(), "ADD R0, R0, R0")]
+        );
+    }
+}
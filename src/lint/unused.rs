@@ -0,0 +1,152 @@
+use crate::ast::processed_ast::{LineColumn, Program, ProgramItem};
+use crate::ast::raw_ast::{Comment, Directive, DirectiveType, Instruction, InstructionType, Label};
+use crate::lint::{Error, ErrorKind, ProgramItemVisitor, RuleLevel};
+use std::collections::HashMap;
+
+/// Labels no instruction refers to, and labelled data no instruction loads,
+/// stores or takes the address of. The entry label is exempt, nothing in the
+/// program needs to refer to it. Off unless enabled, since many programs label
+/// lines only to name them.
+pub struct UnusedLabel {
+    entry_label: Option<String>,
+    // label names referred to, and whether any reference reads or writes memory there
+    references: HashMap<String, bool>,
+}
+
+impl UnusedLabel {
+    pub fn new(entry_label: Option<String>) -> Self {
+        Self {
+            entry_label,
+            references: HashMap::new(),
+        }
+    }
+
+    fn check(&self, labels: &[Label], data: Option<&Directive>) -> Vec<Error> {
+        labels
+            .iter()
+            .filter(|label| self.entry_label.as_deref() != Some(label.name()))
+            .filter_map(|label| {
+                let accessed = self.references.get(label.name());
+                let kind = match data {
+                    Some(directive) if accessed != Some(&true) => ErrorKind::UnreferencedData(
+                        label.name().to_owned(),
+                        directive.content().clone(),
+                    ),
+                    None if accessed.is_none() => ErrorKind::UnusedLabel(label.name().to_owned()),
+                    _ => return None,
+                };
+                Some(Error::new(kind, label.span()))
+            })
+            .collect()
+    }
+}
+
+impl ProgramItemVisitor for UnusedLabel {
+    fn id(&self) -> &'static str {
+        "unused-label"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Off
+    }
+
+    fn prepare(&mut self, program: &Program) {
+        self.references.clear();
+        for item in program.items() {
+            let ProgramItem::Instruction(_, instruction, ..) = item else {
+                continue;
+            };
+            let Some(reference) = instruction.label_reference() else {
+                continue;
+            };
+            let accesses = matches!(
+                instruction.instruction_type(),
+                InstructionType::Ld(..)
+                    | InstructionType::Ldi(..)
+                    | InstructionType::Lea(..)
+                    | InstructionType::St(..)
+                    | InstructionType::Sti(..)
+            );
+            *self
+                .references
+                .entry(reference.content().clone())
+                .or_default() |= accesses;
+        }
+    }
+
+    fn visit_instruction(
+        &mut self,
+        labels: &[Label],
+        _: &Instruction,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        self.check(labels, None)
+    }
+
+    fn visit_directive(
+        &mut self,
+        labels: &[Label],
+        directive: &Directive,
+        _: &Option<Comment>,
+        _: &LineColumn,
+    ) -> Vec<Error> {
+        let data = match directive.directive_type() {
+            DirectiveType::FILL(_) | DirectiveType::BLKW(_) | DirectiveType::STRINGZ(_) => {
+                Some(directive)
+            }
+            DirectiveType::ORIG(_) | DirectiveType::END => None,
+        };
+        self.check(labels, data)
+    }
+
+    fn visit_eol(&mut self, labels: &[Label]) -> Vec<Error> {
+        self.check(labels, None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{RuleLevel, UnusedLabel, lint_errors_with};
+
+    fn unused(content: &str, entry_label: Option<&str>) -> Vec<String> {
+        lint_errors_with(content, |linter| {
+            linter.register(Box::new(UnusedLabel::new(entry_label.map(str::to_owned))));
+            linter.set_level("unused-label", RuleLevel::Warn);
+        })
+        .iter()
+        .filter(|error| *error.rule() == "unused-label")
+        .map(|error| error.kind().to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_unused() {
+        let content = r#".ORIG x3000
+MAIN LEA R0, TEXT
+PUTS
+LD R1, COUNT
+LOOP ADD R1, R1, #-1
+BRp LOOP
+DONE HALT
+TEXT .STRINGZ "hi"
+COUNT .FILL #3
+SPARE .BLKW #1
+TABLE .FILL #1
+.FILL #2
+JSR TABLE
+END
+.END
+"#;
+        assert_eq!(
+            unused(content, Some("MAIN")),
+            vec![
+                "Label `DONE` is never used",
+                "`.BLKW` data `SPARE` is never loaded or stored",
+                "`.FILL` data `TABLE` is never loaded or stored",
+                "Label `END` is never used",
+            ]
+        );
+        assert_eq!(unused(content, None)[0], "Label `MAIN` is never used");
+    }
+}
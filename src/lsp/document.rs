@@ -0,0 +1,360 @@
+use crate::asm::{Assembler, ErrorKind, Listing, SymbolTable};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{DirectiveType, Label, Span};
+use crate::ast::{get_ast, get_ast_recovering};
+use crate::diagnostic::{self, Severity};
+use crate::fmt::{FormatStyle, Formatter};
+use crate::lint::LintConfig;
+use crate::lsp::{
+    Diagnostic, DocumentSymbol, Hover, MarkupContent, Position, Range, SEVERITY_ERROR,
+    SEVERITY_WARNING, SYMBOL_KIND_FUNCTION, SYMBOL_KIND_VARIABLE, TextEdit,
+};
+use crate::semantic::Analyzer;
+
+const SOURCE: &str = "lc3-toolchain";
+
+/// An open text document, every request re-parses the current text.
+#[derive(Debug)]
+pub struct Document {
+    text: String,
+    // byte offset of the start of every line
+    line_starts: Vec<usize>,
+}
+
+// a label name under the cursor, either defined or referenced there
+struct LabelOccurrence {
+    name: String,
+    span: Span,
+    definition: bool,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut character = 0;
+        for (index, c) in self.text[*start..].char_indices() {
+            if character >= position.character as usize || c == '\n' {
+                return start + index;
+            }
+            character += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    pub fn range(&self, span: &Span) -> Range {
+        Range {
+            start: self.position(*span.start()),
+            end: self.position(*span.end()),
+        }
+    }
+
+    /// Syntax errors, or assembler errors and style warnings once the text parses.
+    pub fn diagnostics(&self, lint_config: &LintConfig) -> Vec<Diagnostic> {
+        let (program, errors) = get_ast_recovering(&self.text);
+        let mut diagnostics: Vec<diagnostic::Diagnostic> =
+            errors.iter().map(diagnostic::Diagnostic::from).collect();
+        if diagnostics.is_empty() {
+            if let Err(errors) = Analyzer::new(&program).analyze() {
+                diagnostics.extend(errors.iter().map(diagnostic::Diagnostic::from));
+            }
+            if let Err(errors) = Assembler::new(&program).assemble() {
+                // label and operand range errors are already reported by the semantic analysis
+                let errors = errors.iter().filter(|error| {
+                    !matches!(
+                        error.kind(),
+                        ErrorKind::UndefinedLabel(..)
+                            | ErrorKind::DuplicateLabel(..)
+                            | ErrorKind::OutOfField(..)
+                    )
+                });
+                diagnostics.extend(errors.map(diagnostic::Diagnostic::from));
+            }
+            if let Err(errors) = lint_config.linter(program).check() {
+                diagnostics.extend(errors.iter().map(diagnostic::Diagnostic::from));
+            }
+        }
+        diagnostics
+            .iter()
+            .map(|diagnostic| Diagnostic {
+                range: self.range(diagnostic.span()),
+                severity: match diagnostic.severity() {
+                    Severity::Error => SEVERITY_ERROR,
+                    Severity::Warning => SEVERITY_WARNING,
+                },
+                code: diagnostic.code().to_string(),
+                source: SOURCE.to_string(),
+                message: diagnostic.summary(),
+            })
+            .collect()
+    }
+
+    /// Replaces the whole text with the formatted one, `None` if it does not parse.
+    pub fn format(&self, style: &FormatStyle) -> Option<Vec<TextEdit>> {
+        let program = get_ast(&self.text).ok()?;
+        let mut formatter = Formatter::new(style);
+        formatter.format(program);
+        let formatted = String::from_utf8_lossy(formatter.contents()).into_owned();
+        if formatted == self.text {
+            return Some(vec![]);
+        }
+        Some(vec![TextEdit {
+            range: Range {
+                start: self.position(0),
+                end: self.position(self.text.len()),
+            },
+            new_text: formatted,
+        }])
+    }
+
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let program = get_ast(&self.text).ok()?;
+        let name = self.label_at(&program, position)?.name;
+        label_occurrences(&program)
+            .into_iter()
+            .find(|occurrence| occurrence.definition && occurrence.name == name)
+            .map(|occurrence| self.range(&occurrence.span))
+    }
+
+    pub fn references(&self, position: Position, include_declaration: bool) -> Vec<Range> {
+        let Ok(program) = get_ast(&self.text) else {
+            return vec![];
+        };
+        let Some(name) = self.label_at(&program, position).map(|label| label.name) else {
+            return vec![];
+        };
+        label_occurrences(&program)
+            .into_iter()
+            .filter(|occurrence| occurrence.name == name)
+            .filter(|occurrence| include_declaration || !occurrence.definition)
+            .map(|occurrence| self.range(&occurrence.span))
+            .collect()
+    }
+
+    /// Address of the label under the cursor, or the encoding of the instruction on the line.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let program = get_ast(&self.text).ok()?;
+        if let Some(label) = self.label_at(&program, position) {
+            // labels resolve even when others are duplicated or misplaced
+            let (symbol_table, _) = SymbolTable::resolve(&program);
+            let address = symbol_table.get(&label.name)?;
+            return Some(Hover {
+                contents: markdown(format!("`{}` x{address:04X}", label.name)),
+                range: self.range(&label.span),
+            });
+        }
+        let line = position.line as usize + 1;
+        let span = program.items().iter().find_map(|item| match item {
+            ProgramItem::Instruction(_, instruction, _, lc) if *lc.line() == line => {
+                Some(instruction.span())
+            }
+            ProgramItem::Directive(_, directive, _, lc)
+                if *lc.line() == line
+                    && matches!(directive.directive_type(), DirectiveType::FILL(..)) =>
+            {
+                Some(directive.span())
+            }
+            _ => None,
+        })?;
+        let object = Assembler::new(&program).assemble().ok()?;
+        let listing = Listing::new(&program, &object, &self.text);
+        let row = listing
+            .rows()
+            .iter()
+            .find(|row| *row.line() == Some(line))?;
+        let (address, word) = ((*row.address())?, (*row.word())?);
+        let binary = format!("{word:016b}");
+        let nibbles: Vec<&str> = (0..4)
+            .map(|index| &binary[index * 4..index * 4 + 4])
+            .collect();
+        Some(Hover {
+            contents: markdown(format!(
+                "```\nx{address:04X}: x{word:04X}\n{}\n```",
+                nibbles.join(" ")
+            )),
+            range: self.range(span),
+        })
+    }
+
+    /// Every label, as a function when it marks an instruction and a variable otherwise.
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        let Ok(program) = get_ast(&self.text) else {
+            return vec![];
+        };
+        let (symbol_table, _) = SymbolTable::resolve(&program);
+        let mut symbols = vec![];
+        for item in program.items() {
+            let (labels, kind) = match item {
+                ProgramItem::Instruction(labels, ..) | ProgramItem::EOL(labels) => {
+                    (labels, SYMBOL_KIND_FUNCTION)
+                }
+                ProgramItem::Directive(labels, ..) => (labels, SYMBOL_KIND_VARIABLE),
+                ProgramItem::Comment(..) | ProgramItem::Error(..) => continue,
+            };
+            // labels sharing a line are stored in reverse source order
+            for label in labels.iter().rev() {
+                let range = self.range(label.span());
+                symbols.push(DocumentSymbol {
+                    name: label.name().to_owned(),
+                    detail: symbol_table
+                        .get(label.name())
+                        .map(|address| format!("x{address:04X}")),
+                    kind,
+                    range,
+                    selection_range: range,
+                });
+            }
+        }
+        symbols
+    }
+
+    fn label_at(&self, program: &Program, position: Position) -> Option<LabelOccurrence> {
+        let offset = self.offset(position);
+        label_occurrences(program).into_iter().find(|occurrence| {
+            (*occurrence.span.start()..=*occurrence.span.end()).contains(&offset)
+        })
+    }
+}
+
+fn label_occurrences(program: &Program) -> Vec<LabelOccurrence> {
+    let mut occurrences = vec![];
+    let mut define = |labels: &[Label]| {
+        for label in labels.iter().rev() {
+            // the colon is not part of the name
+            let end = *label.span().start() + label.name().len();
+            occurrences.push(LabelOccurrence {
+                name: label.name().to_owned(),
+                span: Span::new(*label.span().start(), end),
+                definition: true,
+            });
+        }
+    };
+    let mut references = vec![];
+    for item in program.items() {
+        match item {
+            ProgramItem::Instruction(labels, instruction, ..) => {
+                define(labels);
+                if let Some(label) = instruction.label_reference() {
+                    references.push(LabelOccurrence {
+                        name: label.content().clone(),
+                        span: label.span().clone(),
+                        definition: false,
+                    });
+                }
+            }
+            ProgramItem::Directive(labels, ..) | ProgramItem::EOL(labels) => define(labels),
+            ProgramItem::Comment(..) | ProgramItem::Error(..) => {}
+        }
+    }
+    occurrences.append(&mut references);
+    occurrences
+}
+
+fn markdown(value: String) -> MarkupContent {
+    MarkupContent {
+        kind: "markdown".to_string(),
+        value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROGRAM: &str = ".ORIG x3000\nLOOP ADD R0, R0, #1\n    BRp LOOP\n    HALT\n.END\n";
+
+    fn position(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn test_positions() {
+        let document = Document::new("ab\n\u{1F600}c\n".to_string());
+        assert_eq!(document.position(1), position(0, 1));
+        assert_eq!(document.position(3), position(1, 0));
+        // the emoji is two UTF-16 code units but four bytes
+        assert_eq!(document.position(7), position(1, 2));
+        assert_eq!(document.offset(position(1, 2)), 7);
+        assert_eq!(document.offset(position(0, 10)), 2);
+        assert_eq!(document.offset(position(5, 0)), document.text().len());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let document = Document::new(".ORIG x3000\nloop BR MISSING\n.END\n".to_string());
+        let diagnostics = document.diagnostics(&LintConfig::default());
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, SEVERITY_ERROR);
+        assert_eq!(diagnostics[0].message, "Undefined label `MISSING`");
+        assert_eq!(diagnostics[0].code, "S001");
+        assert_eq!(diagnostics[0].range.start, position(1, 8));
+        assert_eq!(diagnostics[1].severity, SEVERITY_WARNING);
+
+        let document = Document::new(".ORIG x3000\nADD R0\n".to_string());
+        let diagnostics = document.diagnostics(&LintConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Syntax error: Expected `Register`");
+    }
+
+    #[test]
+    fn test_navigation() {
+        let document = Document::new(PROGRAM.to_string());
+        let definition = document.definition(position(2, 9));
+        assert_eq!(
+            definition,
+            Some(Range {
+                start: position(1, 0),
+                end: position(1, 4)
+            })
+        );
+        assert_eq!(document.references(position(1, 2), true).len(), 2);
+        assert_eq!(document.references(position(1, 2), false).len(), 1);
+        assert!(document.references(position(3, 5), true).is_empty());
+    }
+
+    #[test]
+    fn test_hover_and_symbols() {
+        let document = Document::new(PROGRAM.to_string());
+        let hover = document.hover(position(2, 5)).unwrap();
+        assert_eq!(
+            hover.contents.value,
+            "```\nx3001: x03FE\n0000 0011 1111 1110\n```"
+        );
+        let hover = document.hover(position(2, 10)).unwrap();
+        assert_eq!(hover.contents.value, "`LOOP` x3000");
+        let symbols = document.symbols();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "LOOP");
+        assert_eq!(symbols[0].detail.as_deref(), Some("x3000"));
+        assert_eq!(symbols[0].kind, SYMBOL_KIND_FUNCTION);
+        // a duplicated label does not hide the address of the others
+        let document = Document::new(format!("{PROGRAM}.ORIG x4000\nLOOP HALT\n.END\n"));
+        let hover = document.hover(position(2, 10)).unwrap();
+        assert_eq!(hover.contents.value, "`LOOP` x3000");
+    }
+}
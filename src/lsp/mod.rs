@@ -0,0 +1,9 @@
+mod document;
+mod protocol;
+mod server;
+mod transport;
+
+pub use document::*;
+pub use protocol::*;
+pub use server::*;
+pub use transport::*;
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+// the subset of the Language Server Protocol types used by the server
+
+pub const SEVERITY_ERROR: u8 = 1;
+pub const SEVERITY_WARNING: u8 = 2;
+
+pub const SYMBOL_KIND_FUNCTION: u8 = 12;
+pub const SYMBOL_KIND_VARIABLE: u8 = 13;
+
+/// Zero-based line and UTF-16 column.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: u8,
+    pub code: String,
+    pub source: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub kind: u8,
+    pub range: Range,
+    pub selection_range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hover {
+    pub contents: MarkupContent,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkupContent {
+    pub kind: String,
+    pub value: String,
+}
@@ -0,0 +1,399 @@
+use crate::config::find_config;
+use crate::fmt::{self, FormatStyle};
+use crate::lint::{self, LintConfig};
+use crate::lsp::{Document, Position, read_message, write_message};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_REQUEST: i64 = -32600;
+
+// full document sync
+const TEXT_DOCUMENT_SYNC_FULL: u8 = 1;
+
+/// Language server over JSON-RPC, keeping the open documents in memory.
+/// Every document is formatted and linted with the `lc3-format.toml` and
+/// `lc3-lint.toml` nearest to it, or to the workspace root.
+pub struct Server {
+    documents: HashMap<String, Document>,
+    // used where no configuration file is found
+    format_style: FormatStyle,
+    lint_config: LintConfig,
+    root: Option<PathBuf>,
+    shutdown: bool,
+    exit: bool,
+}
+
+impl Server {
+    pub fn new(format_style: FormatStyle, lint_config: LintConfig) -> Self {
+        Self {
+            documents: HashMap::new(),
+            format_style,
+            lint_config,
+            root: None,
+            shutdown: false,
+            exit: false,
+        }
+    }
+
+    /// Serves until the `exit` notification or end of input, returns whether
+    /// `shutdown` was requested first.
+    pub fn run(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<bool> {
+        while !self.exit {
+            let message = match read_message(input) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    write_message(output, &error_response(Value::Null, PARSE_ERROR, err))?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            for reply in self.handle(message) {
+                write_message(output, &reply)?;
+            }
+        }
+        Ok(self.shutdown)
+    }
+
+    /// Handles one message, returning the responses and notifications to send.
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // responses to server requests are never expected
+            return match id {
+                Some(id) => vec![error_response(id, INVALID_REQUEST, "Missing method")],
+                None => vec![],
+            };
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match id {
+            Some(id) => vec![match self.request(method, &params) {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                Err((code, message)) => error_response(id, code, message),
+            }],
+            None => self.notification(method, &params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shutdown && method != "shutdown" {
+            return Err((INVALID_REQUEST, "Server is shutting down".to_string()));
+        }
+        match method {
+            "initialize" => {
+                self.root = params["workspaceFolders"][0]["uri"]
+                    .as_str()
+                    .or(params["rootUri"].as_str())
+                    .and_then(uri_path)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+                Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+                    "documentFormattingProvider": true,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "hoverProvider": true,
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": {
+                    "name": "lc3-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/formatting" => {
+                let (uri, document) = self.document(params)?;
+                Ok(json!(document.format(&self.format_style(uri))))
+            }
+            "textDocument/definition" => {
+                let (uri, document) = self.document(params)?;
+                let range = document.definition(position(params)?);
+                Ok(json!(
+                    range.map(|range| json!({"uri": uri, "range": range}))
+                ))
+            }
+            "textDocument/references" => {
+                let (uri, document) = self.document(params)?;
+                let include_declaration = params["context"]["includeDeclaration"]
+                    .as_bool()
+                    .unwrap_or(true);
+                let ranges = document.references(position(params)?, include_declaration);
+                Ok(json!(
+                    ranges
+                        .iter()
+                        .map(|range| json!({"uri": uri, "range": range}))
+                        .collect::<Vec<_>>()
+                ))
+            }
+            "textDocument/hover" => {
+                let (_, document) = self.document(params)?;
+                Ok(json!(document.hover(position(params)?)))
+            }
+            "textDocument/documentSymbol" => {
+                let (_, document) = self.document(params)?;
+                Ok(json!(document.symbols()))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method `{method}`"))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().map(str::to_owned);
+        match (method, uri) {
+            ("exit", _) => {
+                self.exit = true;
+                vec![]
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents
+                    .insert(uri.clone(), Document::new(text.to_owned()));
+                vec![self.publish_diagnostics(&uri)]
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // with full sync the last change holds the whole text
+                let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                else {
+                    return vec![];
+                };
+                self.documents
+                    .insert(uri.clone(), Document::new(text.to_owned()));
+                vec![self.publish_diagnostics(&uri)]
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": {"uri": uri, "diagnostics": []},
+                })]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn document<'a>(&'a self, params: &'a Value) -> Result<(&'a str, &'a Document), (i64, String)> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((INVALID_PARAMS, "Missing text document".to_string()))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or((INVALID_PARAMS, format!("Document `{uri}` is not open")))?;
+        Ok((uri, document))
+    }
+
+    // the nearest configuration file to the document, then to the workspace root
+    fn config_path(&self, uri: &str, filename: &str) -> Option<PathBuf> {
+        uri_path(uri)
+            .and_then(|path| find_config(path.parent()?, filename))
+            .or_else(|| find_config(self.root.as_ref()?, filename))
+    }
+
+    fn format_style(&self, uri: &str) -> FormatStyle {
+        let Some(path) = self.config_path(uri, fmt::CONFIG_FILENAME) else {
+            return self.format_style;
+        };
+        fmt::read_style(&path).unwrap_or_else(|err| {
+            eprintln!("{err}, fallback to the default settings");
+            self.format_style
+        })
+    }
+
+    fn lint_config(&self, uri: &str) -> LintConfig {
+        let Some(path) = self.config_path(uri, lint::CONFIG_FILENAME) else {
+            return self.lint_config.clone();
+        };
+        LintConfig::read(&path).unwrap_or_else(|err| {
+            eprintln!("{err}, fallback to the default settings");
+            self.lint_config.clone()
+        })
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = self
+            .documents
+            .get(uri)
+            .map(|document| document.diagnostics(&self.lint_config(uri)))
+            .unwrap_or_default();
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics},
+        })
+    }
+}
+
+// local path of a `file://` URI, percent-decoded
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    // skip the authority, usually empty or `localhost`
+    let path = &path[path.find('/')?..];
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `/C:/dir` on Windows
+    let path = match path.get(2..3) {
+        Some(":") if cfg!(windows) => &path[1..],
+        _ => &path,
+    };
+    Some(Path::new(path).to_path_buf())
+}
+
+fn position(params: &Value) -> Result<Position, (i64, String)> {
+    serde_json::from_value(params["position"].clone())
+        .map_err(|err| (INVALID_PARAMS, format!("Invalid position: {err}")))
+}
+
+fn error_response(id: Value, code: i64, message: impl ToString) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message.to_string()},
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fmt::DEFAULT_STYLE as DEFAULT_FORMAT_STYLE;
+
+    const URI: &str = "file:///test.asm";
+
+    fn open(server: &mut Server, text: &str) -> Vec<Value> {
+        server.handle(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": URI, "languageId": "lc3", "version": 1, "text": text}},
+        }))
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut server = Server::new(DEFAULT_FORMAT_STYLE, LintConfig::default());
+        let replies =
+            server.handle(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}));
+        assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+        let replies = server.handle(json!({"jsonrpc": "2.0", "id": 2, "method": "bogus"}));
+        assert_eq!(replies[0]["error"]["code"], METHOD_NOT_FOUND);
+        let replies = server.handle(json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}));
+        assert_eq!(replies[0]["result"], Value::Null);
+        assert!(
+            server
+                .handle(json!({"jsonrpc": "2.0", "method": "exit"}))
+                .is_empty()
+        );
+        assert!(server.exit);
+    }
+
+    #[test]
+    fn test_documents() {
+        let mut server = Server::new(DEFAULT_FORMAT_STYLE, LintConfig::default());
+        let replies = open(&mut server, ".ORIG x3000\nBR MISSING\n.END\n");
+        assert_eq!(replies[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(
+            replies[0]["params"]["diagnostics"][0]["message"],
+            "Undefined label `MISSING`"
+        );
+        let replies = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/definition",
+            "params": {"textDocument": {"uri": URI}, "position": {"line": 1, "character": 4}},
+        }));
+        assert_eq!(replies[0]["result"], Value::Null);
+        let replies = server.handle(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": {"uri": URI, "version": 2},
+                "contentChanges": [{"text": ".ORIG x3000\nBR MISSING\nMISSING HALT\n.END\n"}],
+            },
+        }));
+        assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+        let replies = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/definition",
+            "params": {"textDocument": {"uri": URI}, "position": {"line": 1, "character": 4}},
+        }));
+        assert_eq!(replies[0]["result"]["range"]["start"]["line"], 2);
+        let replies = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/hover",
+            "params": {"textDocument": {"uri": "file:///closed.asm"}, "position": {"line": 0, "character": 0}},
+        }));
+        assert_eq!(replies[0]["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_configuration() {
+        let directory = format!("{}/test/data/lsp", env!("CARGO_MANIFEST_DIR"));
+        let text = ".ORIG x3000\nADD R0, R0, #1\nHALT\n.END\n";
+        let requests = |server: &mut Server, uri: &str| {
+            let replies = server.handle(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {"textDocument": {"uri": uri, "languageId": "lc3", "version": 1, "text": text}},
+            }));
+            let diagnostics = replies[0]["params"]["diagnostics"].clone();
+            let replies = server.handle(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "textDocument/formatting",
+                "params": {"textDocument": {"uri": uri}, "options": {}},
+            }));
+            (diagnostics, replies[0]["result"][0]["newText"].clone())
+        };
+
+        let mut server = Server::new(DEFAULT_FORMAT_STYLE, LintConfig::default());
+        let (diagnostics, formatted) = requests(&mut server, URI);
+        assert_eq!(diagnostics[0]["code"], "L009");
+        assert!(formatted.as_str().unwrap().contains("\n    ADD"));
+
+        // found from a directory below the one holding the files
+        let uri = format!("file://{directory}/nested%20dir/test.asm");
+        let (diagnostics, formatted) = requests(&mut server, &uri);
+        assert_eq!(diagnostics, json!([]));
+        assert!(formatted.as_str().unwrap().contains("\n        ADD"));
+
+        // documents that are not files use the workspace root
+        server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "initialize",
+            "params": {"rootUri": format!("file://{directory}")},
+        }));
+        let (diagnostics, _) = requests(&mut server, "untitled:Untitled-1");
+        assert_eq!(diagnostics, json!([]));
+    }
+}
@@ -0,0 +1,64 @@
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Reads one `Content-Length` framed JSON-RPC message, `None` at end of input.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            // tolerate blank lines between messages
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length =
+                    Some(value.trim().parse::<usize>().map_err(|err| {
+                        io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+                    })?);
+            }
+        }
+    }
+    let mut content = vec![0; content_length.unwrap_or_default()];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let mut buffer = vec![];
+        write_message(&mut buffer, &json!({"jsonrpc": "2.0", "id": 1})).unwrap();
+        write_message(&mut buffer, &json!({"method": "exit"})).unwrap();
+        assert!(buffer.starts_with(b"Content-Length: 24\r\n\r\n{\"id\":1,"));
+        let mut input = buffer.as_slice();
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(json!({"jsonrpc": "2.0", "id": 1}))
+        );
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(json!({"method": "exit"}))
+        );
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+}
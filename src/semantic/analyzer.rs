@@ -0,0 +1,272 @@
+use crate::asm::{Field, item_addresses};
+use crate::ast::processed_ast::{Program, ProgramItem};
+use crate::ast::raw_ast::{InstructionType, Label};
+use crate::semantic::{Error, ErrorKind};
+use either::Either;
+use std::collections::HashMap;
+
+/// Label definitions in source order, without addresses.
+#[derive(Debug, Default)]
+pub struct LabelTable {
+    labels: Vec<Label>,
+    indices: HashMap<String, usize>,
+    // lowercase name to the first definition spelled that way
+    folded: HashMap<String, usize>,
+}
+
+impl LabelTable {
+    /// Collects every definition, reporting duplicates and case collisions.
+    pub fn new(program: &Program) -> (Self, Vec<Error>) {
+        let mut table = Self::default();
+        let mut errors = vec![];
+        for item in program.items() {
+            let labels = match item {
+                ProgramItem::Instruction(labels, ..)
+                | ProgramItem::Directive(labels, ..)
+                | ProgramItem::EOL(labels) => labels,
+                ProgramItem::Comment(..) | ProgramItem::Error(..) => continue,
+            };
+            // labels sharing a line are stored in reverse source order
+            for label in labels.iter().rev() {
+                table.define(label, &mut errors);
+            }
+        }
+        (table, errors)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Label> {
+        self.indices.get(name).map(|index| &self.labels[*index])
+    }
+
+    /// Definition whose name only matches when case is ignored.
+    pub fn get_ignore_case(&self, name: &str) -> Option<&Label> {
+        self.folded
+            .get(&name.to_lowercase())
+            .map(|index| &self.labels[*index])
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    fn define(&mut self, label: &Label, errors: &mut Vec<Error>) {
+        let name = label.name();
+        if let Some(first) = self.get(name) {
+            errors.push(Error::new(
+                ErrorKind::DuplicateLabel(name.to_owned(), first.span().clone()),
+                label.span(),
+            ));
+            return;
+        }
+        if let Some(other) = self.get_ignore_case(name) {
+            errors.push(Error::new(
+                ErrorKind::CaseCollision(
+                    name.to_owned(),
+                    other.name().to_owned(),
+                    other.span().clone(),
+                ),
+                label.span(),
+            ));
+        }
+        self.indices.insert(name.to_owned(), self.labels.len());
+        self.folded
+            .entry(name.to_lowercase())
+            .or_insert(self.labels.len());
+        self.labels.push(label.clone());
+    }
+}
+
+/// Checks label definitions and references without assembling the program.
+pub struct Analyzer<'a> {
+    program: &'a Program,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self { program }
+    }
+
+    pub fn analyze(&self) -> Result<(), Vec<Error>> {
+        let (table, mut errors) = LabelTable::new(self.program);
+        self.check_references(&table, &mut errors);
+        self.check_ranges(&mut errors);
+        errors.sort_by_key(|error| *error.span().start());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn check_references(&self, table: &LabelTable, errors: &mut Vec<Error>) {
+        for item in self.program.items() {
+            let ProgramItem::Instruction(_, instruction, ..) = item else {
+                continue;
+            };
+            let Some(reference) = instruction.label_reference() else {
+                continue;
+            };
+            if table.get(reference.content()).is_none() {
+                let similar = table
+                    .get_ignore_case(reference.content())
+                    .map(|label| label.name().to_owned());
+                errors.push(Error::new(
+                    ErrorKind::UndefinedLabel(reference.content().to_owned(), similar),
+                    reference.span(),
+                ));
+            }
+        }
+    }
+
+    // operand fields against the addresses the assembler would assign
+    fn check_ranges(&self, errors: &mut Vec<Error>) {
+        let addresses = item_addresses(self.program);
+        let mut targets: HashMap<&str, u16> = HashMap::new();
+        for (item, address) in self.program.items().iter().zip(&addresses) {
+            let (ProgramItem::Instruction(labels, ..)
+            | ProgramItem::Directive(labels, ..)
+            | ProgramItem::EOL(labels)) = item
+            else {
+                continue;
+            };
+            if let Some(address) = address {
+                for label in labels {
+                    // duplicates resolve to the first definition
+                    targets.entry(label.name()).or_insert(*address);
+                }
+            }
+        }
+        for (item, address) in self.program.items().iter().zip(addresses) {
+            let ProgramItem::Instruction(_, instruction, ..) = item else {
+                continue;
+            };
+            let Some(field) = Field::of(instruction) else {
+                continue;
+            };
+            let (checked, span) = match instruction.pc_offset() {
+                Some(Either::Left(reference)) => {
+                    let (Some(address), Some(target)) =
+                        (address, targets.get(reference.content().as_str()))
+                    else {
+                        continue;
+                    };
+                    let offset = *target as i32 - (address as i32 + 1);
+                    (
+                        field.check_offset(reference.content(), offset),
+                        reference.span(),
+                    )
+                }
+                Some(Either::Right(offset)) => (field.check(*offset.value()), offset.span()),
+                None => match instruction.instruction_type() {
+                    InstructionType::Trap(vector) => {
+                        (field.check(*vector.value() as i32), vector.span())
+                    }
+                    InstructionType::Add(_, _, Either::Right(immediate))
+                    | InstructionType::And(_, _, Either::Right(immediate))
+                    | InstructionType::Ldr(_, _, immediate)
+                    | InstructionType::Str(_, _, immediate) => {
+                        (field.check(*immediate.value()), immediate.span())
+                    }
+                    _ => continue,
+                },
+            };
+            if let Err(field_error) = checked {
+                errors.push(Error::new(ErrorKind::OutOfField(field_error), span));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asm::FieldError;
+    use crate::ast::get_ast;
+
+    fn analyze(content: &str) -> Vec<ErrorKind> {
+        let program = get_ast(content);
+        assert!(program.is_ok());
+        match Analyzer::new(&program.unwrap()).analyze() {
+            Ok(()) => vec![],
+            Err(errors) => errors.iter().map(|error| error.kind().clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_resolved() {
+        let content = r#"
+        .ORIG x3000
+        LD R0, DATA
+        LOOP: BRp LOOP
+        JSR SUB
+        HALT
+        SUB RET
+        DATA .FILL #1
+        .END"#;
+        assert!(analyze(content).is_empty());
+    }
+
+    #[test]
+    fn test_undefined() {
+        let errors = analyze(".ORIG x3000 BR NOWHERE LEA R0, loop LOOP HALT .END");
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], ErrorKind::UndefinedLabel(name, None) if name == "NOWHERE"));
+        assert!(matches!(
+            &errors[1],
+            ErrorKind::UndefinedLabel(name, Some(similar)) if name == "loop" && similar == "LOOP"
+        ));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let content = r#"
+        .ORIG x3000
+        ADD R0, R0, #16
+        AND R0, R0, #-16
+        LDR R0, R1, #-33
+        TRAP x100
+        BR FAR
+        JSR FAR
+        .BLKW #255
+        FAR HALT
+        LD R0, #-257
+        .END"#;
+        let errors = analyze(content);
+        assert_eq!(errors.len(), 5);
+        assert!(matches!(
+            errors[0],
+            ErrorKind::OutOfField(FieldError::Immediate(Field::Imm5, 16))
+        ));
+        assert!(matches!(
+            errors[1],
+            ErrorKind::OutOfField(FieldError::Immediate(Field::Offset6, -33))
+        ));
+        assert!(matches!(
+            errors[2],
+            ErrorKind::OutOfField(FieldError::Immediate(Field::TrapVect8, 0x100))
+        ));
+        assert!(matches!(
+            &errors[3],
+            ErrorKind::OutOfField(FieldError::Offset(Field::PcOffset9, label, 256)) if label == "FAR"
+        ));
+        assert!(matches!(
+            errors[4],
+            ErrorKind::OutOfField(FieldError::Immediate(Field::PcOffset9, -257))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_and_collision() {
+        let content = ".ORIG x3000\nLOOP ADD R0, R0, #1\nLOOP: BR LOOP\nLoop HALT\n.END";
+        let errors = analyze(content);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            ErrorKind::DuplicateLabel(name, first) if name == "LOOP" && *first.start() == 12
+        ));
+        assert!(matches!(
+            &errors[1],
+            ErrorKind::CaseCollision(name, other, _) if name == "Loop" && other == "LOOP"
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::asm::FieldError;
+use crate::ast::raw_ast::Span;
+use crate::diagnostic::{Diagnostic, Fix};
+use getset::Getters;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ErrorKind {
+    // name, definition differing only in case
+    UndefinedLabel(String, Option<String>),
+    // name, first definition
+    DuplicateLabel(String, Span),
+    // name, colliding name, its definition
+    CaseCollision(String, String, Span),
+    OutOfField(FieldError),
+}
+
+#[derive(Debug, Getters)]
+pub struct Error {
+    #[get = "pub"]
+    kind: ErrorKind,
+    #[get = "pub"]
+    span: Span,
+}
+
+impl Error {
+    pub(super) fn new(kind: ErrorKind, span: &Span) -> Self {
+        Self {
+            kind,
+            span: span.clone(),
+        }
+    }
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::UndefinedLabel(..) => "S001",
+            ErrorKind::DuplicateLabel(..) => "S002",
+            ErrorKind::CaseCollision(..) => "S003",
+            ErrorKind::OutOfField(FieldError::Immediate(..)) => "S004",
+            ErrorKind::OutOfField(FieldError::Offset(..)) => "S005",
+        }
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let diagnostic = Diagnostic::error(error.kind().code(), error.kind(), error.span());
+        match error.kind() {
+            ErrorKind::UndefinedLabel(_, None) => diagnostic.with_label("Label is never defined"),
+            ErrorKind::UndefinedLabel(_, Some(similar)) => diagnostic
+                .with_label("Label is never defined")
+                .with_note(format!("A label named `{similar}` exists"))
+                .with_fix(Fix::new(
+                    format!("Use `{similar}`"),
+                    vec![(error.span().clone(), similar.clone())],
+                )),
+            ErrorKind::DuplicateLabel(_, first) => diagnostic
+                .with_label("Label is defined again here")
+                .with_secondary(first, "First defined here"),
+            ErrorKind::CaseCollision(_, _, other) => diagnostic
+                .with_label("Label is defined here")
+                .with_secondary(other, "Differently cased label defined here")
+                .with_note("Some assemblers ignore the case of labels"),
+            ErrorKind::OutOfField(field_error) => field_error.annotate(diagnostic),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UndefinedLabel(label, _) => write!(f, "Undefined label `{label}`"),
+            ErrorKind::DuplicateLabel(label, _) => write!(f, "Duplicate label `{label}`"),
+            ErrorKind::CaseCollision(label, other, _) => {
+                write!(f, "Label `{label}` only differs in case from `{other}`")
+            }
+            ErrorKind::OutOfField(field_error) => write!(f, "{field_error}"),
+        }
+    }
+}
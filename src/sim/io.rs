@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+
+/// Character device behind the keyboard and display registers.
+pub trait Io {
+    /// Returns the next input character, or `None` if no key is available.
+    fn read(&mut self) -> Option<u8>;
+    fn write(&mut self, byte: u8);
+}
+
+/// Deterministic device fed from a fixed input buffer, collecting all output.
+#[derive(Debug, Default)]
+pub struct BufferIo {
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl BufferIo {
+    pub fn new(input: &[u8]) -> Self {
+        Self {
+            input: input.iter().copied().collect(),
+            output: vec![],
+        }
+    }
+
+    pub fn push_input(&mut self, input: &[u8]) {
+        self.input.extend(input);
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+impl Io for BufferIo {
+    fn read(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn write(&mut self, byte: u8) {
+        self.output.push(byte);
+    }
+}
+
+/// Terminal device, reads block on stdin.
+#[derive(Debug, Default)]
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn read(&mut self) -> Option<u8> {
+        let mut byte = [0u8];
+        match std::io::stdin().read(&mut byte) {
+            Ok(1) => Some(byte[0]),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, byte: u8) {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(&[byte]);
+        let _ = stdout.flush();
+    }
+}
@@ -0,0 +1,767 @@
+use crate::sim::Io;
+use std::fmt;
+
+pub const KBSR: u16 = 0xFE00;
+pub const KBDR: u16 = 0xFE02;
+pub const DSR: u16 = 0xFE04;
+pub const DDR: u16 = 0xFE06;
+pub const MCR: u16 = 0xFFFE;
+
+const INTERRUPT_TABLE: u16 = 0x0100;
+const KEYBOARD_VECTOR: u8 = 0x80;
+const KEYBOARD_PRIORITY: u16 = 4;
+const USER_SPACE_START: u16 = 0x3000;
+const DEVICE_SPACE_START: u16 = 0xFE00;
+const IN_PROMPT: &[u8] = b"\nInput a character> ";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exception {
+    PrivilegeMode,
+    IllegalOpcode,
+    AccessControl,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    InvalidObject,
+    InputExhausted,
+    UnhandledTrap(u8),
+    UnhandledException(Exception),
+    UnhandledInterrupt(u8),
+    StepLimit(usize),
+}
+
+// aborts the current instruction
+enum Fault {
+    Exception(Exception),
+    Error(Error),
+}
+
+/// LC-3 machine state, memory-mapped devices are backed by `I`.
+pub struct Machine<I: Io> {
+    memory: Vec<u16>,
+    registers: [u16; 8],
+    pc: u16,
+    psr: u16,
+    saved_usp: u16,
+    saved_ssp: u16,
+    mcr: u16,
+    kbsr: u16,
+    kbdr: u16,
+    io: I,
+}
+
+impl<I: Io> Machine<I> {
+    pub fn new(io: I) -> Self {
+        Self {
+            memory: vec![0; 0x10000],
+            registers: [0; 8],
+            pc: USER_SPACE_START,
+            // supervisor mode, priority 0, Z set
+            psr: 0x0002,
+            saved_usp: DEVICE_SPACE_START,
+            saved_ssp: USER_SPACE_START,
+            mcr: 0x8000,
+            kbsr: 0,
+            kbdr: 0,
+            io,
+        }
+    }
+
+    /// Loads a big-endian `.obj` image and points the PC at its origin.
+    pub fn load_obj(&mut self, bytes: &[u8]) -> Result<u16, Error> {
+        if bytes.len() < 2 || bytes.len() % 2 != 0 {
+            return Err(Error::InvalidObject);
+        }
+        let mut words = bytes
+            .chunks_exact(2)
+            .map(|word| u16::from_be_bytes([word[0], word[1]]));
+        let origin = words.next().unwrap();
+        let words: Vec<u16> = words.collect();
+        if origin as usize + words.len() > 0x10000 {
+            return Err(Error::InvalidObject);
+        }
+        self.load(origin, &words);
+        Ok(origin)
+    }
+
+    pub fn load(&mut self, origin: u16, words: &[u16]) {
+        let origin = origin as usize;
+        self.memory[origin..origin + words.len()].copy_from_slice(words);
+        self.pc = origin as u16;
+    }
+
+    pub fn registers(&self) -> &[u16; 8] {
+        &self.registers
+    }
+
+    pub fn set_register(&mut self, register: usize, value: u16) {
+        self.registers[register] = value;
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    pub fn psr(&self) -> u16 {
+        self.psr
+    }
+
+    pub fn is_user_mode(&self) -> bool {
+        self.psr & 0x8000 != 0
+    }
+
+    pub fn set_user_mode(&mut self, user: bool) {
+        if user {
+            self.psr |= 0x8000;
+        } else {
+            self.psr &= !0x8000;
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.mcr & 0x8000 == 0
+    }
+
+    pub fn io(&self) -> &I {
+        &self.io
+    }
+
+    pub fn io_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    /// Reads memory without triggering device side effects.
+    pub fn peek(&self, address: u16) -> u16 {
+        match address {
+            KBSR => self.kbsr,
+            KBDR => self.kbdr,
+            DSR => 0x8000,
+            DDR => 0,
+            MCR => self.mcr,
+            _ => self.memory[address as usize],
+        }
+    }
+
+    /// Writes memory without triggering device side effects.
+    pub fn poke(&mut self, address: u16, value: u16) {
+        match address {
+            MCR => self.mcr = value,
+            _ => self.memory[address as usize] = value,
+        }
+    }
+
+    /// Runs until the machine halts, failing after `max_steps` instructions.
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, Error> {
+        for steps in 0..max_steps {
+            if self.is_halted() {
+                return Ok(steps);
+            }
+            self.step()?;
+        }
+        if self.is_halted() {
+            Ok(max_steps)
+        } else {
+            Err(Error::StepLimit(max_steps))
+        }
+    }
+
+    /// Executes a single instruction, or enters a pending interrupt.
+    pub fn step(&mut self) -> Result<(), Error> {
+        if self.is_halted() {
+            return Ok(());
+        }
+        if self.keyboard_interrupt_pending() {
+            return self.enter_supervisor(
+                KEYBOARD_VECTOR,
+                Some(KEYBOARD_PRIORITY),
+                Error::UnhandledInterrupt(KEYBOARD_VECTOR),
+            );
+        }
+        let result = self.fetch().and_then(|ir| self.execute(ir));
+        match result {
+            Ok(()) => Ok(()),
+            Err(Fault::Exception(exception)) => self.raise(exception),
+            Err(Fault::Error(error)) => Err(error),
+        }
+    }
+
+    fn fetch(&mut self) -> Result<u16, Fault> {
+        let ir = self.read(self.pc)?;
+        self.pc = self.pc.wrapping_add(1);
+        Ok(ir)
+    }
+
+    fn execute(&mut self, ir: u16) -> Result<(), Fault> {
+        let dr = ((ir >> 9) & 0x7) as usize;
+        let sr1 = ((ir >> 6) & 0x7) as usize;
+        match ir >> 12 {
+            // BR
+            0b0000 => {
+                if (ir >> 9) & self.psr & 0x7 != 0 {
+                    self.pc = self.pc.wrapping_add(sext(ir, 9));
+                }
+            }
+            // ADD
+            0b0001 => {
+                let value = self.registers[sr1].wrapping_add(self.arithmetic_operand(ir));
+                self.set_register_cc(dr, value);
+            }
+            // LD
+            0b0010 => {
+                let value = self.read(self.pc.wrapping_add(sext(ir, 9)))?;
+                self.set_register_cc(dr, value);
+            }
+            // ST
+            0b0011 => self.write(self.pc.wrapping_add(sext(ir, 9)), self.registers[dr])?,
+            // JSR, JSRR
+            0b0100 => {
+                let return_address = self.pc;
+                self.pc = if ir & 0x0800 != 0 {
+                    self.pc.wrapping_add(sext(ir, 11))
+                } else {
+                    self.registers[sr1]
+                };
+                self.registers[7] = return_address;
+            }
+            // AND
+            0b0101 => {
+                let value = self.registers[sr1] & self.arithmetic_operand(ir);
+                self.set_register_cc(dr, value);
+            }
+            // LDR
+            0b0110 => {
+                let value = self.read(self.registers[sr1].wrapping_add(sext(ir, 6)))?;
+                self.set_register_cc(dr, value);
+            }
+            // STR
+            0b0111 => self.write(
+                self.registers[sr1].wrapping_add(sext(ir, 6)),
+                self.registers[dr],
+            )?,
+            // RTI
+            0b1000 => {
+                if self.is_user_mode() {
+                    return Err(Fault::Exception(Exception::PrivilegeMode));
+                }
+                self.pc = self.pop();
+                self.psr = self.pop();
+                if self.is_user_mode() {
+                    self.saved_ssp = self.registers[6];
+                    self.registers[6] = self.saved_usp;
+                }
+            }
+            // NOT
+            0b1001 => self.set_register_cc(dr, !self.registers[sr1]),
+            // LDI
+            0b1010 => {
+                let pointer = self.read(self.pc.wrapping_add(sext(ir, 9)))?;
+                let value = self.read(pointer)?;
+                self.set_register_cc(dr, value);
+            }
+            // STI
+            0b1011 => {
+                let pointer = self.read(self.pc.wrapping_add(sext(ir, 9)))?;
+                self.write(pointer, self.registers[dr])?;
+            }
+            // JMP, RET
+            0b1100 => self.pc = self.registers[sr1],
+            // reserved
+            0b1101 => return Err(Fault::Exception(Exception::IllegalOpcode)),
+            // LEA
+            0b1110 => self.set_register_cc(dr, self.pc.wrapping_add(sext(ir, 9))),
+            // TRAP
+            0b1111 => {
+                let vector = (ir & 0xFF) as u8;
+                // every routine can return with RET, those of an OS image with RTI as well
+                self.registers[7] = self.pc;
+                match self.memory[vector as usize] {
+                    0 => self.native_trap(vector)?,
+                    routine => self.switch_to_supervisor(routine, None),
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // service routines used when no operating system image provides one
+    fn native_trap(&mut self, vector: u8) -> Result<(), Fault> {
+        match vector {
+            // GETC
+            0x20 => {
+                let character = self.io.read().ok_or(Fault::Error(Error::InputExhausted))?;
+                self.registers[0] = character as u16;
+            }
+            // OUT
+            0x21 => self.io.write(self.registers[0] as u8),
+            // PUTS
+            0x22 => {
+                let mut address = self.registers[0];
+                loop {
+                    let character = self.read(address)?;
+                    if character == 0 {
+                        break;
+                    }
+                    self.io.write(character as u8);
+                    address = address.wrapping_add(1);
+                }
+            }
+            // IN
+            0x23 => {
+                IN_PROMPT.iter().for_each(|byte| self.io.write(*byte));
+                let character = self.io.read().ok_or(Fault::Error(Error::InputExhausted))?;
+                self.io.write(character);
+                self.io.write(b'\n');
+                self.registers[0] = character as u16;
+            }
+            // PUTSP
+            0x24 => {
+                let mut address = self.registers[0];
+                loop {
+                    let word = self.read(address)?;
+                    if word == 0 {
+                        break;
+                    }
+                    self.io.write(word as u8);
+                    if word >> 8 != 0 {
+                        self.io.write((word >> 8) as u8);
+                    }
+                    address = address.wrapping_add(1);
+                }
+            }
+            // HALT
+            0x25 => self.mcr &= 0x7FFF,
+            _ => return Err(Fault::Error(Error::UnhandledTrap(vector))),
+        }
+        Ok(())
+    }
+
+    fn raise(&mut self, exception: Exception) -> Result<(), Error> {
+        let vector = match exception {
+            Exception::PrivilegeMode => 0x00,
+            Exception::IllegalOpcode => 0x01,
+            Exception::AccessControl => 0x02,
+        };
+        self.enter_supervisor(vector, None, Error::UnhandledException(exception))
+    }
+
+    // enters the handler of an exception or interrupt vector
+    fn enter_supervisor(
+        &mut self,
+        vector: u8,
+        priority: Option<u16>,
+        unhandled: Error,
+    ) -> Result<(), Error> {
+        let handler = self.memory[(INTERRUPT_TABLE + vector as u16) as usize];
+        if handler == 0 {
+            return Err(unhandled);
+        }
+        self.switch_to_supervisor(handler, priority);
+        Ok(())
+    }
+
+    // switches to the supervisor stack, pushes PSR and PC and jumps to `handler`
+    fn switch_to_supervisor(&mut self, handler: u16, priority: Option<u16>) {
+        let psr = self.psr;
+        if self.is_user_mode() {
+            self.saved_usp = self.registers[6];
+            self.registers[6] = self.saved_ssp;
+        }
+        self.psr &= !0x8000;
+        if let Some(priority) = priority {
+            self.psr = (self.psr & !0x0700) | (priority << 8);
+        }
+        self.push(psr);
+        self.push(self.pc);
+        self.pc = handler;
+    }
+
+    fn keyboard_interrupt_pending(&mut self) -> bool {
+        if self.kbsr & 0x4000 == 0 || (self.psr >> 8) & 0x7 >= KEYBOARD_PRIORITY {
+            return false;
+        }
+        self.poll_keyboard();
+        self.kbsr & 0x8000 != 0
+    }
+
+    fn poll_keyboard(&mut self) {
+        if self.kbsr & 0x8000 == 0 {
+            if let Some(character) = self.io.read() {
+                self.kbdr = character as u16;
+                self.kbsr |= 0x8000;
+            }
+        }
+    }
+
+    fn read(&mut self, address: u16) -> Result<u16, Fault> {
+        self.check_access(address)?;
+        Ok(match address {
+            KBSR => {
+                self.poll_keyboard();
+                self.kbsr
+            }
+            KBDR => {
+                self.kbsr &= !0x8000;
+                self.kbdr
+            }
+            _ => self.peek(address),
+        })
+    }
+
+    fn write(&mut self, address: u16, value: u16) -> Result<(), Fault> {
+        self.check_access(address)?;
+        match address {
+            KBSR => self.kbsr = (self.kbsr & 0x8000) | (value & 0x4000),
+            DDR => self.io.write(value as u8),
+            KBDR | DSR => {}
+            _ => self.poke(address, value),
+        }
+        Ok(())
+    }
+
+    fn check_access(&self, address: u16) -> Result<(), Fault> {
+        if self.is_user_mode() && !(USER_SPACE_START..DEVICE_SPACE_START).contains(&address) {
+            return Err(Fault::Exception(Exception::AccessControl));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, value: u16) {
+        self.registers[6] = self.registers[6].wrapping_sub(1);
+        self.memory[self.registers[6] as usize] = value;
+    }
+
+    fn pop(&mut self) -> u16 {
+        let value = self.memory[self.registers[6] as usize];
+        self.registers[6] = self.registers[6].wrapping_add(1);
+        value
+    }
+
+    fn arithmetic_operand(&self, ir: u16) -> u16 {
+        if ir & 0x20 != 0 {
+            sext(ir, 5)
+        } else {
+            self.registers[(ir & 0x7) as usize]
+        }
+    }
+
+    fn set_register_cc(&mut self, register: usize, value: u16) {
+        self.registers[register] = value;
+        let cc = if value == 0 {
+            0b010
+        } else if value & 0x8000 != 0 {
+            0b100
+        } else {
+            0b001
+        };
+        self.psr = (self.psr & !0x7) | cc;
+    }
+}
+
+impl From<Exception> for Fault {
+    fn from(value: Exception) -> Self {
+        Fault::Exception(value)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidObject => write!(f, "invalid object file"),
+            Error::InputExhausted => write!(f, "no more input available"),
+            Error::UnhandledTrap(vector) => write!(f, "no service routine for TRAP x{vector:02X}"),
+            Error::UnhandledException(exception) => {
+                write!(f, "no handler for {exception:?} exception")
+            }
+            Error::UnhandledInterrupt(vector) => {
+                write!(f, "no handler for interrupt x{vector:02X}")
+            }
+            Error::StepLimit(steps) => write!(f, "did not halt within {steps} instructions"),
+        }
+    }
+}
+
+// sign extends the lowest `bits` bits
+fn sext(value: u16, bits: u32) -> u16 {
+    let shift = 16 - bits;
+    (((value << shift) as i16) >> shift) as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asm::Assembler;
+    use crate::ast::get_ast;
+    use crate::sim::BufferIo;
+
+    fn machine(content: &str, input: &[u8]) -> Machine<BufferIo> {
+        let program = get_ast(content);
+        assert!(program.is_ok());
+        let program = program.unwrap();
+        let object = Assembler::new(&program).assemble();
+        assert!(object.is_ok());
+        let mut machine = Machine::new(BufferIo::new(input));
+        for section in object.unwrap().sections() {
+            let loaded = machine.load_obj(&section.to_obj());
+            assert!(loaded.is_ok());
+        }
+        machine
+    }
+
+    fn run(content: &str, input: &[u8]) -> Machine<BufferIo> {
+        let mut machine = machine(content, input);
+        if let Err(err) = machine.run(10_000) {
+            panic!("{err}");
+        }
+        machine
+    }
+
+    #[test]
+    fn test_arithmetic_loop() {
+        let content = r#"
+        .ORIG x3000
+        AND R0, R0, #0
+        ADD R1, R0, #5
+        LOOP ADD R0, R0, R1
+        ADD R1, R1, #-1
+        BRp LOOP
+        NOT R2, R0
+        LEA R3, DATA
+        LDR R4, R3, #0
+        HALT
+        DATA .FILL #-7
+        .END"#;
+        let machine = run(content, b"");
+        assert_eq!(machine.registers()[0], 15);
+        assert_eq!(machine.registers()[2], !15);
+        assert_eq!(machine.registers()[3], 0x3009);
+        assert_eq!(machine.registers()[4], (-7i16) as u16);
+        // LDR of a negative value sets N
+        assert_eq!(machine.psr() & 0x7, 0b100);
+    }
+
+    #[test]
+    fn test_memory() {
+        let content = r#"
+        .ORIG x3000
+        LD R0, VALUE
+        ST R0, COPY
+        LDI R1, POINTER
+        ADD R1, R1, #1
+        STI R1, POINTER
+        HALT
+        VALUE .FILL x1234
+        COPY .BLKW #1
+        POINTER .FILL x3009
+        VALUE_ADDRESS .FILL #41
+        .END"#;
+        let machine = run(content, b"");
+        assert_eq!(machine.peek(0x3007), 0x1234);
+        assert_eq!(machine.peek(0x3009), 42);
+    }
+
+    #[test]
+    fn test_subroutine() {
+        let content = r#"
+        .ORIG x3000
+        JSR DOUBLE
+        LEA R2, DOUBLE
+        JSRR R2
+        HALT
+        DOUBLE ADD R0, R0, #2
+        RET
+        .END"#;
+        let machine = run(content, b"");
+        assert_eq!(machine.registers()[0], 4);
+        // HALT is a TRAP and clobbers R7 as well
+        assert_eq!(machine.registers()[7], 0x3004);
+    }
+
+    #[test]
+    fn test_native_traps() {
+        let content = r#"
+        .ORIG x3000
+        LEA R0, HELLO
+        PUTS
+        GETC
+        OUT
+        IN
+        HALT
+        HELLO .STRINGZ "Hi "
+        .END"#;
+        let machine = run(content, b"ab");
+        assert_eq!(
+            String::from_utf8_lossy(machine.io().output()),
+            "Hi a\nInput a character> b\n"
+        );
+        assert_eq!(machine.registers()[0], b'b' as u16);
+        assert!(machine.is_halted());
+    }
+
+    #[test]
+    fn test_input_exhausted() {
+        let mut machine = machine(".ORIG x3000 GETC HALT .END", b"");
+        assert_eq!(machine.run(10), Err(Error::InputExhausted));
+    }
+
+    #[test]
+    fn test_device_registers() {
+        let content = r#"
+        .ORIG x3000
+        POLL LDI R1, KBSR_ADDR
+        BRzp POLL
+        LDI R0, KBDR_ADDR
+        WAIT LDI R1, DSR_ADDR
+        BRzp WAIT
+        STI R0, DDR_ADDR
+        AND R2, R2, #0
+        STI R2, MCR_ADDR
+        KBSR_ADDR .FILL xFE00
+        KBDR_ADDR .FILL xFE02
+        DSR_ADDR .FILL xFE04
+        DDR_ADDR .FILL xFE06
+        MCR_ADDR .FILL xFFFE
+        .END"#;
+        let machine = run(content, b"z");
+        assert_eq!(machine.io().output(), b"z");
+        assert_eq!(machine.peek(KBSR) & 0x8000, 0);
+    }
+
+    #[test]
+    fn test_user_mode_exceptions() {
+        let mut privileged = machine(".ORIG x3000 RTI .END", b"");
+        privileged.set_user_mode(true);
+        assert_eq!(
+            privileged.step(),
+            Err(Error::UnhandledException(Exception::PrivilegeMode))
+        );
+
+        let mut machine = machine(
+            ".ORIG x3000 LDI R0, POINTER HALT POINTER .FILL x0200 .END",
+            b"",
+        );
+        machine.set_user_mode(true);
+        assert_eq!(
+            machine.step(),
+            Err(Error::UnhandledException(Exception::AccessControl))
+        );
+    }
+
+    #[test]
+    fn test_exception_handler() {
+        let content = r#"
+        .ORIG x3000
+        .FILL xD000
+        HALT
+        HANDLER ADD R5, R5, #1
+        RTI
+        .END"#;
+        let mut machine = machine(content, b"");
+        machine.poke(INTERRUPT_TABLE + 1, 0x3002);
+        machine.set_user_mode(true);
+        machine.set_register(6, 0x4000);
+        assert!(machine.step().is_ok());
+        assert!(!machine.is_user_mode());
+        assert_eq!(machine.pc(), 0x3002);
+        assert!(machine.run(10).is_ok());
+        assert_eq!(machine.registers()[5], 1);
+        assert_eq!(machine.registers()[6], 0x4000);
+        assert!(machine.is_user_mode());
+    }
+
+    #[test]
+    fn test_user_mode_trap() {
+        let content = r#"
+        .ORIG x3000
+        TRAP x30
+        HALT
+        .END
+        .ORIG x1000
+        ADD R5, R5, #1
+        RTI
+        .END"#;
+        let mut trap = machine(content, b"");
+        trap.poke(0x0030, 0x1000);
+        trap.set_pc(0x3000);
+        trap.set_user_mode(true);
+        trap.set_register(6, 0x4000);
+        assert!(trap.step().is_ok());
+        assert!(!trap.is_user_mode());
+        assert_eq!(trap.pc(), 0x1000);
+        // the return address goes on the supervisor stack and into R7
+        assert_eq!(trap.registers()[7], 0x3001);
+        assert_eq!(trap.registers()[6], USER_SPACE_START - 2);
+        assert_eq!(trap.peek(USER_SPACE_START - 2), 0x3001);
+        assert!(trap.run(10).is_ok());
+        assert_eq!(trap.registers()[5], 1);
+        assert_eq!(trap.registers()[6], 0x4000);
+        assert!(trap.is_user_mode());
+
+        let mut puts = machine(
+            ".ORIG x3000 LD R0, POINTER PUTS HALT POINTER .FILL x0200 .END",
+            b"",
+        );
+        puts.set_user_mode(true);
+        puts.poke(0x0200, b'!' as u16);
+        assert!(puts.step().is_ok());
+        assert_eq!(
+            puts.step(),
+            Err(Error::UnhandledException(Exception::AccessControl))
+        );
+        assert!(puts.io().output().is_empty());
+    }
+
+    #[test]
+    fn test_trap_routine_ret() {
+        let content = r#"
+        .ORIG x3000
+        TRAP x30
+        ADD R1, R1, #1
+        HALT
+        .END
+        .ORIG x1000
+        ADD R5, R5, #1
+        RET
+        .END"#;
+        let mut trap = machine(content, b"");
+        trap.poke(0x0030, 0x1000);
+        trap.set_pc(0x3000);
+        trap.set_register(6, 0x5000);
+        assert!(trap.run(10).is_ok());
+        assert_eq!(trap.registers()[5], 1);
+        // back at the caller, past the TRAP
+        assert_eq!(trap.registers()[1], 1);
+    }
+
+    #[test]
+    fn test_keyboard_interrupt() {
+        let content = r#"
+        .ORIG x3000
+        LD R1, ENABLE
+        STI R1, KBSR_ADDR
+        SPIN BRnzp SPIN
+        HANDLER LDI R0, KBDR_ADDR
+        HALT
+        ENABLE .FILL x4000
+        KBSR_ADDR .FILL xFE00
+        KBDR_ADDR .FILL xFE02
+        .END"#;
+        let mut machine = machine(content, b"k");
+        machine.poke(INTERRUPT_TABLE + KEYBOARD_VECTOR as u16, 0x3003);
+        assert!(machine.run(10).is_ok());
+        assert_eq!(machine.registers()[0], b'k' as u16);
+    }
+
+    #[test]
+    fn test_invalid_object() {
+        let mut machine = Machine::new(BufferIo::default());
+        assert_eq!(machine.load_obj(&[0x30]), Err(Error::InvalidObject));
+        assert_eq!(machine.load_obj(&[0x30, 0x00, 0x12, 0x34]), Ok(0x3000));
+        assert_eq!(machine.peek(0x3000), 0x1234);
+    }
+}
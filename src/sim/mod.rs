@@ -0,0 +1,5 @@
+mod io;
+mod machine;
+
+pub use io::*;
+pub use machine::*;
@@ -40,11 +40,9 @@ START:
     BRnzp ALWAYS                                                           ;Branch always (all conditions)
     JMP R7                                                                 ;Jump to address in register
     JSR SUBROUTINE                                                         ;Jump to subroutine
+    JSRR R6                                                                ;Jump to subroutine in register
 
-;Jump to subroutine in register                                            
 ;Control Instructions                                                      
-R6:
-JSRR:
     NOP                                                                    ;No operation
     RET                                                                    ;Return from subroutine
     HALT                                                                   ;Halt execution
@@ -0,0 +1,9 @@
+; Code no path reaches and registers read before they are written
+
+.ORIG x3000
+    ADD R1, R1, #1           ; R1 holds whatever was left in it
+    BRNZP DONE
+    ADD R2, R2, #1           ; after the branch, no label leads here
+DONE
+    HALT
+.END
@@ -0,0 +1,12 @@
+; Operands that do not fit their field and labels that do not resolve
+
+.ORIG x3000
+    AND R0, R0, #0
+    ADD R0, R0, #16          ; imm5 is -16..15
+    LD R1, MISSING           ; never defined
+    BRZ FAR                  ; more than 256 words away
+    HALT
+    .BLKW #300
+FAR
+    HALT
+.END
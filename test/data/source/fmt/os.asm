@@ -0,0 +1,25 @@
+; Operating system code with trap and interrupt service routines
+.ORIG x0500
+; Keyboard interrupt service routine
+KBD_ISR:
+   ST R0, SAVE_R0     ; save registers
+  ST R1,SAVE_R1
+LDI R0, KBDR_PTR    ; read the key, clearing KBSR ready
+    STI R0,   DDR_PTR
+LD R1, SAVE_R1
+  LD R0, SAVE_R0
+rti                 ; restore PC and PSR from the supervisor stack
+
+; Illegal opcode exception
+ILLEGAL_ISR
+    LEA R0, ILLEGAL_MSG
+  PUTS
+    RTI
+RESERVED .FILL xD000   ; reserved opcode, only reachable through .FILL
+
+SAVE_R0 .BLKW 1
+SAVE_R1 .BLKW 1
+KBDR_PTR .FILL xFE02
+DDR_PTR .FILL xFE06
+ILLEGAL_MSG .STRINGZ "Illegal opcode"
+.END
@@ -0,0 +1,12 @@
+; Hand-encoded PC offsets next to labels
+.ORIG x3000
+LOOP   LD R0,#2    ; numeric offset
+  LDI R1, x1
+LEA   R2,LOOP
+ST R0,  #-4
+    STI R1 ,x0
+BRnzp   #-6     ; back to LOOP
+JSR #-1024
+   BRz LOOP
+HALT
+.END
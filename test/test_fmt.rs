@@ -117,6 +117,16 @@ mod test_fmt {
         assert_true(&DEFAULT_STYLE, "fmt/all.asm")
     }
 
+    #[test]
+    fn test_os() {
+        assert_true(&DEFAULT_STYLE, "fmt/os.asm")
+    }
+
+    #[test]
+    fn test_pc_offsets() {
+        assert_true(&DEFAULT_STYLE, "fmt/pc_offsets.asm")
+    }
+
     #[test]
     fn test_empty() {
         assert_true(&DEFAULT_STYLE, "fmt/empty.asm")
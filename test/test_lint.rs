@@ -2,16 +2,95 @@
 mod lint_test {
     use std::process::Command;
 
-    #[test]
-    fn test_all() {
+    // exit code and the line and code of every diagnostic, in the order reported
+    fn lint(path: &str) -> (Option<i32>, Vec<(u64, String)>) {
         let mut process = Command::new("cargo");
         let output = process
             .arg("run")
             .arg("--bin")
             .arg("lc3lint")
-            .arg("test/data/expected/lint/all.asm")
+            .arg(path)
+            .arg("--output-format")
+            .arg("json")
             .output();
         assert!(output.is_ok());
-        assert!(output.unwrap().status.success());
+        let output = output.unwrap();
+        let records: serde_json::Value = if output.stdout.is_empty() {
+            serde_json::Value::Array(vec![])
+        } else {
+            serde_json::from_slice(&output.stdout).unwrap()
+        };
+        let diagnostics = records
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| {
+                (
+                    record["line"].as_u64().unwrap(),
+                    record["code"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        (output.status.code(), diagnostics)
+    }
+
+    fn expected(diagnostics: &[(u64, &str)]) -> Vec<(u64, String)> {
+        diagnostics
+            .iter()
+            .map(|(line, code)| (*line, code.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_all() {
+        // every syntax element in the expected style, the operands are not meant to run
+        let (status, diagnostics) = lint("test/data/expected/lint/all.asm");
+        assert_eq!(status, Some(1));
+        assert!(
+            diagnostics
+                .iter()
+                .all(|(_, code)| !["L001", "L002", "L003", "L004"].contains(&code.as_str())),
+            "{diagnostics:?}"
+        );
+        assert_eq!(
+            diagnostics,
+            expected(&[
+                (18, "S004"),
+                (85, "S004"),
+                (87, "S004"),
+                (92, "S001"),
+                (93, "S001"),
+                (94, "S001"),
+                (95, "S001"),
+                (96, "S001"),
+                (97, "S001"),
+                (16, "L009"),
+                (16, "L009"),
+                (18, "L009"),
+                (20, "L009"),
+                (22, "L009"),
+                (24, "L009"),
+                (38, "L006"),
+                (72, "L006"),
+                (91, "L006"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_semantic() {
+        let (status, diagnostics) = lint("test/data/expected/lint/semantic.asm");
+        assert_eq!(status, Some(1));
+        assert_eq!(
+            diagnostics,
+            expected(&[(5, "S004"), (6, "S001"), (7, "S005")])
+        );
+    }
+
+    #[test]
+    fn test_flow() {
+        let (status, diagnostics) = lint("test/data/expected/lint/flow.asm");
+        assert_eq!(status, Some(2));
+        assert_eq!(diagnostics, expected(&[(4, "L009"), (6, "L006")]));
     }
 }
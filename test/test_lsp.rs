@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod lsp_test {
+    use lc3_toolchain::ast::get_ast;
+    use lc3_toolchain::fmt::{DEFAULT_STYLE, Formatter};
+    use serde_json::{Value, json};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    const URI: &str = "file:///test/data/lsp.asm";
+    const SOURCE: &str = ".ORIG x3000\nLOOP ADD R0, R0, #1\n    BRp LOOP\n    HALT\n.END\n";
+
+    fn frame(message: Value) -> Vec<u8> {
+        let content = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{content}", content.len()).into_bytes()
+    }
+
+    fn parse_frames(mut output: &str) -> Vec<Value> {
+        let mut messages = vec![];
+        while let Some(start) = output.find("\r\n\r\n") {
+            let length: usize = output[..start]
+                .trim()
+                .strip_prefix("Content-Length: ")
+                .unwrap()
+                .parse()
+                .unwrap();
+            let body = &output[start + 4..start + 4 + length];
+            messages.push(serde_json::from_str(body).unwrap());
+            output = &output[start + 4 + length..];
+        }
+        messages
+    }
+
+    fn request(id: i64, method: &str, line: u32, character: u32) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": {
+                "textDocument": {"uri": URI},
+                "position": {"line": line, "character": character},
+                "context": {"includeDeclaration": true},
+            },
+        })
+    }
+
+    #[test]
+    fn test_session() {
+        let script = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {"textDocument": {"uri": URI, "languageId": "lc3", "version": 1, "text": SOURCE}},
+            }),
+            request(2, "textDocument/definition", 2, 9),
+            request(3, "textDocument/references", 1, 1),
+            request(4, "textDocument/hover", 1, 6),
+            json!({"jsonrpc": "2.0", "id": 5, "method": "textDocument/documentSymbol", "params": {"textDocument": {"uri": URI}}}),
+            json!({"jsonrpc": "2.0", "id": 6, "method": "textDocument/formatting", "params": {"textDocument": {"uri": URI}, "options": {}}}),
+            json!({"jsonrpc": "2.0", "id": 7, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ];
+        let mut child = Command::new(env!("CARGO_BIN_EXE_lc3-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        for message in script {
+            stdin.write_all(&frame(message)).unwrap();
+        }
+        drop(stdin);
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+
+        let messages = parse_frames(&String::from_utf8(output.stdout).unwrap());
+        let response = |id: i64| {
+            messages
+                .iter()
+                .find(|message| message["id"] == id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            response(1)["result"]["capabilities"]["definitionProvider"],
+            true
+        );
+        let diagnostics = messages
+            .iter()
+            .find(|message| message["method"] == "textDocument/publishDiagnostics")
+            .unwrap();
+        // R0 is read before it is set, and `BRp` is mixed case, the only style warning
+        let diagnostics = diagnostics["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0]["code"], "L009");
+        assert_eq!(
+            diagnostics[0]["range"]["start"],
+            json!({"line": 1, "character": 13})
+        );
+        assert_eq!(diagnostics[1]["code"], "L003");
+        assert_eq!(diagnostics[1]["severity"], 2);
+        assert_eq!(
+            diagnostics[1]["range"]["start"],
+            json!({"line": 2, "character": 4})
+        );
+        assert_eq!(
+            response(2)["result"]["range"]["start"],
+            json!({"line": 1, "character": 0})
+        );
+        assert_eq!(response(3)["result"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            response(4)["result"]["contents"]["value"],
+            "```\nx3000: x1021\n0001 0000 0010 0001\n```"
+        );
+        assert_eq!(response(5)["result"][0]["name"], "LOOP");
+        // formatting on save matches `lc3fmt` with its default style
+        let mut formatter = Formatter::new(&DEFAULT_STYLE);
+        formatter.format(get_ast(SOURCE).unwrap());
+        assert_eq!(
+            response(6)["result"][0]["newText"],
+            String::from_utf8_lossy(formatter.contents()).as_ref()
+        );
+        assert_eq!(response(7)["result"], Value::Null);
+    }
+}